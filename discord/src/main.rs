@@ -2,15 +2,40 @@
 #![allow(clippy::missing_errors_doc)]
 
 use dotenvy::dotenv;
+use shared::error::Error as ApiError;
+use shared::money::Money;
+use shared::types::{HistoryParams, LeaderboardParams, BURN_ACCOUNT, MINT_ACCOUNT};
 use shared::AekosiaAPI;
 use reqwest::Client;
+use std::collections::HashMap;
 use std::env;
-use env_logger;
+use std::fmt::Write;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{event, Level};
+use uuid::Uuid;
 
 use poise::serenity_prelude as serenity;
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
-type Context<'a> = poise::Context<'a, AekosiaAPI, Error>;
+type Context<'a> = poise::Context<'a, Data, Error>;
+
+pub struct Data {
+    api: AekosiaAPI,
+    money_admins: MoneyAdmins,
+}
+
+/// Who is allowed to print and burn money, and where it gets logged
+struct MoneyAdmins {
+    roles: Vec<serenity::RoleId>,
+    users: Vec<serenity::UserId>,
+    audit_channel: Option<serenity::ChannelId>,
+}
+
+const CONFIRM_TIMEOUT: Duration = Duration::from_mins(1);
+const HISTORY_TIMEOUT: Duration = Duration::from_mins(5);
+const HISTORY_PAGE_SIZE: i64 = 10;
+const LEADERBOARD_PAGE_SIZE: i64 = 10;
 
 /// Displays your or another user's account creation date
 #[poise::command(slash_command, prefix_command)]
@@ -24,16 +49,360 @@ async fn age(
     Ok(())
 }
 
-/// Gets the balance of a individual
+/// Gets the balance of the account a individual is currently using
 #[poise::command(slash_command, prefix_command, track_edits)]
 async fn balance(
     ctx: Context<'_>,
     #[description = "Selected person"] user: Option<serenity::User>,
 ) -> Result<(), Error> {
     let user = user.as_ref().unwrap_or_else(|| ctx.author()).id.as_u64();
-    let id = ctx.data().get_id_from_discord(user).await?;
-    let balance = ctx.data().get_balance(&id).await?;
-    ctx.say(balance.to_string()).await?;
+    let id = ctx.data().api.get_id_from_discord(user).await?;
+    let active = ctx.data().api.get_active_balance(&id).await?;
+    let balance = Money(active.balance);
+
+    let response = if active.account == id {
+        format!("{balance} (personal account)")
+    } else {
+        let account = ctx.data().api.get_account(&active.account).await?;
+        format!("{balance} (using account {})", account.name)
+    };
+
+    ctx.say(response).await?;
+    Ok(())
+}
+
+/// Prints or burns money, only for the configured roles and users
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("money_print", "money_burn"),
+    check = "is_money_admin"
+)]
+async fn money(ctx: Context<'_>) -> Result<(), Error> {
+    poise::builtins::help(ctx, Some("money"), poise::builtins::HelpConfiguration::default()).await?;
+    Ok(())
+}
+
+/// Prints new money into someone's personal account
+#[poise::command(slash_command, prefix_command, rename = "print")]
+async fn money_print(
+    ctx: Context<'_>,
+    #[description = "Person to print money for"] user: serenity::User,
+    #[description = "Amount to print, for example 1.5"] amount: String,
+    #[description = "Why the money is printed"] reason: String,
+) -> Result<(), Error> {
+    let amount = Money::from_str(&amount)?;
+    let id = ctx.data().api.get_id_from_discord(user.id.as_u64()).await?;
+    let receipt = ctx.data().api.print_money(&id, &amount, &reason).await?;
+
+    log_money_action(ctx, &format!("printed {amount} for {}: {reason} (receipt `{}`)", user.tag(), receipt.id.as_simple())).await;
+
+    ctx.say(format!("Printed {amount} for {}, they now have {}", user.name, Money(receipt.to_balance))).await?;
+    Ok(())
+}
+
+/// Takes money out of circulation from someone's personal account
+#[poise::command(slash_command, prefix_command, rename = "burn")]
+async fn money_burn(
+    ctx: Context<'_>,
+    #[description = "Person to burn money from"] user: serenity::User,
+    #[description = "Amount to burn, for example 1.5"] amount: String,
+    #[description = "Why the money is burned"] reason: String,
+) -> Result<(), Error> {
+    let amount = Money::from_str(&amount)?;
+    let id = ctx.data().api.get_id_from_discord(user.id.as_u64()).await?;
+    let receipt = ctx.data().api.burn_money(&id, &amount, &reason).await?;
+
+    log_money_action(ctx, &format!("burned {amount} from {}: {reason} (receipt `{}`)", user.tag(), receipt.id.as_simple())).await;
+
+    ctx.say(format!("Burned {amount} from {}, they have {} left", user.name, Money(receipt.from_balance))).await?;
+    Ok(())
+}
+
+async fn is_money_admin(ctx: Context<'_>) -> Result<bool, Error> {
+    let admins = &ctx.data().money_admins;
+
+    let allowed = admins.users.contains(&ctx.author().id)
+        || match ctx.author_member().await {
+            Some(member) => member.roles.iter().any(|role| admins.roles.contains(role)),
+            None => false,
+        };
+
+    if !allowed {
+        ctx.say("You are not allowed to print or burn money").await?;
+    }
+
+    Ok(allowed)
+}
+
+/// Posts what happened to the audit channel, if there is one. The money has already
+/// moved by then, so failing to post is logged instead of failing the command.
+async fn log_money_action(ctx: Context<'_>, action: &str) {
+    if let Some(channel) = ctx.data().money_admins.audit_channel {
+        if let Err(err) = channel.say(ctx, format!("{} {action}", ctx.author().tag())).await {
+            event!(Level::ERROR, %err, action, "Could not post to the money audit channel");
+        }
+    }
+}
+
+/// Pays someone from the account you are currently using, after you confirm it
+#[poise::command(slash_command, prefix_command)]
+async fn pay(
+    ctx: Context<'_>,
+    #[description = "Person to pay"] user: serenity::User,
+    #[description = "Amount to pay, for example 1.5"] amount: String,
+    #[description = "What the payment is for"] reason: Option<String>,
+) -> Result<(), Error> {
+    let amount = Money::from_str(&amount)?;
+    let actor = ctx.data().api.get_id_from_discord(ctx.author().id.as_u64()).await?;
+    let to = ctx.data().api.get_id_from_discord(user.id.as_u64()).await?;
+
+    let ctx_id = ctx.id();
+    let confirm_id = format!("{ctx_id}confirm");
+    let cancel_id = format!("{ctx_id}cancel");
+
+    let reply = ctx
+        .send(|m| {
+            m.content(format!("Pay {amount} to {}?", user.name)).components(|c| {
+                c.create_action_row(|row| {
+                    row.create_button(|b| b.custom_id(&confirm_id).label("Confirm").style(serenity::ButtonStyle::Success))
+                        .create_button(|b| b.custom_id(&cancel_id).label("Cancel").style(serenity::ButtonStyle::Danger))
+                })
+            })
+        })
+        .await?;
+
+    let press = serenity::CollectComponentInteraction::new(ctx)
+        .author_id(ctx.author().id)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(CONFIRM_TIMEOUT)
+        .await;
+
+    let response = match press {
+        Some(press) if press.data.custom_id == confirm_id => {
+            press
+                .create_interaction_response(ctx, |r| r.kind(serenity::InteractionResponseType::DeferredUpdateMessage))
+                .await?;
+
+            match ctx.data().api.payment(&actor, None, &to, &amount, reason.as_deref(), None).await {
+                Ok(receipt) => format!(
+                    "Paid {amount} to {}, you have {} left (receipt `{}`)",
+                    user.name,
+                    Money(receipt.from_balance),
+                    receipt.id.as_simple()
+                ),
+                Err(ApiError::InsufficientFunds(_)) => "You lack the funds to send this payment".to_string(),
+                Err(err) => format!("The payment failed: {err}"),
+            }
+        }
+        Some(press) => {
+            press
+                .create_interaction_response(ctx, |r| r.kind(serenity::InteractionResponseType::DeferredUpdateMessage))
+                .await?;
+
+            "Cancelled the payment".to_string()
+        }
+        None => "Nothing was paid, the confirmation timed out".to_string(),
+    };
+
+    // Removes the buttons so the payment can't be confirmed twice
+    reply.edit(ctx, |m| m.content(response).components(|c| c)).await?;
+    Ok(())
+}
+
+/// Shows the recent transactions of the account you or someone else is currently using
+#[poise::command(slash_command, prefix_command)]
+async fn history(
+    ctx: Context<'_>,
+    #[description = "Selected person"] user: Option<serenity::User>,
+) -> Result<(), Error> {
+    let user = user.as_ref().unwrap_or_else(|| ctx.author());
+    let id = ctx.data().api.get_id_from_discord(user.id.as_u64()).await?;
+    let account = ctx.data().api.get_active_balance(&id).await?.account;
+    let title = format!("History of {}", user.name);
+
+    let ctx_id = ctx.id();
+    let prev_id = format!("{ctx_id}prev");
+    let next_id = format!("{ctx_id}next");
+
+    // The cursor of every page up to the current one, so previous pages can be fetched again
+    let mut cursors = vec![None];
+    let mut page = history_page(ctx, &account, None).await?;
+
+    let reply = ctx
+        .send(|m| {
+            m.embed(|e| e.title(&title).description(&page.description))
+                .components(|c| history_buttons(c, &prev_id, &next_id, false, page.next_cursor.is_some()))
+        })
+        .await?;
+
+    while let Some(press) = serenity::CollectComponentInteraction::new(ctx)
+        .author_id(ctx.author().id)
+        .filter(move |press| press.data.custom_id.starts_with(&ctx_id.to_string()))
+        .timeout(HISTORY_TIMEOUT)
+        .await
+    {
+        if press.data.custom_id == next_id {
+            if let Some(cursor) = page.next_cursor {
+                cursors.push(Some(cursor));
+            }
+        } else if cursors.len() > 1 {
+            cursors.pop();
+        }
+
+        let cursor = cursors.last().copied().flatten();
+        page = history_page(ctx, &account, cursor).await?;
+
+        press
+            .create_interaction_response(ctx, |r| {
+                r.kind(serenity::InteractionResponseType::UpdateMessage).interaction_response_data(|d| {
+                    d.embed(|e| e.title(&title).description(&page.description)).components(|c| {
+                        history_buttons(c, &prev_id, &next_id, cursors.len() > 1, page.next_cursor.is_some())
+                    })
+                })
+            })
+            .await?;
+    }
+
+    reply.edit(ctx, |m| m.components(|c| c)).await?;
+    Ok(())
+}
+
+struct RenderedPage {
+    description: String,
+    next_cursor: Option<Uuid>,
+}
+
+async fn history_page(ctx: Context<'_>, account: &Uuid, cursor: Option<Uuid>) -> Result<RenderedPage, Error> {
+    let params = HistoryParams {
+        cursor,
+        limit: Some(HISTORY_PAGE_SIZE),
+        ..Default::default()
+    };
+    let page = ctx.data().api.get_history(account, &params).await?;
+
+    let mut labels: HashMap<Uuid, String> = HashMap::new();
+    let mut description = String::new();
+
+    for entry in &page.entries {
+        let amount = entry.amount_for(account);
+        let sign = if amount > 0 { "+" } else { "" };
+
+        let mut counterparties = Vec::new();
+        for posting in entry.postings.iter().filter(|posting| posting.account != *account) {
+            let label = match labels.get(&posting.account) {
+                Some(label) => label.clone(),
+                None => account_label(&ctx.data().api, &posting.account).await?,
+            };
+            labels.insert(posting.account, label.clone());
+            counterparties.push(label);
+        }
+
+        write!(description, "<t:{}:R> **{sign}{}** {}", entry.time, Money(amount), counterparties.join(", "))?;
+        if let Some(memo) = &entry.memo {
+            write!(description, " - {memo}")?;
+        }
+        if entry.reverses.is_some() {
+            write!(description, " (reversal)")?;
+        }
+        writeln!(description)?;
+    }
+
+    if description.is_empty() {
+        description.push_str("No transactions yet");
+    }
+
+    Ok(RenderedPage { description, next_cursor: page.next_cursor })
+}
+
+/// Mentions people and names other accounts
+async fn account_label(api: &AekosiaAPI, account: &Uuid) -> Result<String, Error> {
+    if *account == MINT_ACCOUNT {
+        return Ok("the mint".to_string());
+    } else if *account == BURN_ACCOUNT {
+        return Ok("burned".to_string());
+    }
+
+    match api.get_person(account).await {
+        Ok(person) => Ok(format!("<@{}>", person.discord_id)),
+        Err(ApiError::NotFound(_)) => Ok(api.get_account(account).await?.name),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn history_buttons<'a>(
+    components: &'a mut serenity::CreateComponents,
+    prev_id: &str,
+    next_id: &str,
+    has_prev: bool,
+    has_next: bool,
+) -> &'a mut serenity::CreateComponents {
+    components.create_action_row(|row| {
+        row.create_button(|b| b.custom_id(prev_id).label("Previous").disabled(!has_prev))
+            .create_button(|b| b.custom_id(next_id).label("Next").disabled(!has_next))
+    })
+}
+
+/// Shows who has the most money, along with your own rank
+#[poise::command(slash_command, prefix_command)]
+async fn leaderboard(
+    ctx: Context<'_>,
+    #[description = "Page to show, starting at 1"] page: Option<i64>,
+) -> Result<(), Error> {
+    let page = page.unwrap_or(1).max(1);
+
+    // Unregistered people can still look at the leaderboard
+    let person = match ctx.data().api.get_id_from_discord(ctx.author().id.as_u64()).await {
+        Ok(id) => Some(id),
+        Err(ApiError::NotFound(_)) => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    let params = LeaderboardParams {
+        limit: Some(LEADERBOARD_PAGE_SIZE),
+        offset: Some((page - 1).saturating_mul(LEADERBOARD_PAGE_SIZE)),
+        person,
+    };
+    let leaderboard = ctx.data().api.leaderboard(&params).await?;
+
+    let mut description = String::new();
+    for entry in &leaderboard.entries {
+        writeln!(description, "**{}.** <@{}> {}", entry.rank, entry.discord_id, Money(entry.balance))?;
+    }
+
+    if description.is_empty() {
+        description.push_str("Nobody is on this page");
+    }
+
+    if let Some(own) = &leaderboard.person {
+        write!(description, "\nYou are **#{}** with {}", own.rank, Money(own.balance))?;
+    }
+
+    ctx.send(|m| m.embed(|e| e.title(format!("Leaderboard (page {page})")).description(description)))
+        .await?;
+    Ok(())
+}
+
+/// Shows statistics about the economy as a whole
+#[poise::command(slash_command, prefix_command)]
+async fn stats(ctx: Context<'_>) -> Result<(), Error> {
+    let stats = ctx.data().api.get_stats().await?;
+
+    ctx.send(|m| {
+        m.embed(|e| {
+            e.title("Economy")
+                .field("Money supply", Money(stats.money_supply), true)
+                .field("Minted", Money(stats.minted), true)
+                .field("Burned", Money(stats.burned), true)
+                .field("Accounts", stats.accounts, true)
+                .field("People", stats.persons, true)
+                .field("Median balance", Money(stats.median_balance), true)
+                .field("Transactions (day / week)", format!("{} / {}", stats.transactions_day, stats.transactions_week), true)
+                .field("Volume (day / week)", format!("{} / {}", Money(stats.volume_day), Money(stats.volume_week)), true)
+                .field("Gini coefficient", format!("{:.3}", stats.gini), true)
+        })
+    })
+    .await?;
     Ok(())
 }
 
@@ -43,11 +412,132 @@ async fn register(
     ctx: Context<'_>
 ) -> Result<(), Error> {
     let user = ctx.author().id.as_u64();
-    ctx.data().register_person(user).await?;
-    ctx.say("Registered!").await?;
+
+    let response = match ctx.data().api.register_person(user).await {
+        Ok(_) => "Registered!",
+        Err(ApiError::AlreadyRegistered(_)) => "You are already registered!",
+        Err(err) => return Err(err.into()),
+    };
+
+    ctx.say(response).await?;
+    Ok(())
+}
+
+/// Manage your friends, friends marked as accountant can spend from your account
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands("friend_list", "friend_add", "friend_accept", "friend_decline", "friend_remove", "friend_accountant")
+)]
+async fn friend(ctx: Context<'_>) -> Result<(), Error> {
+    poise::builtins::help(ctx, Some("friend"), poise::builtins::HelpConfiguration::default()).await?;
+    Ok(())
+}
+
+/// Lists your friends and pending friend requests
+#[poise::command(slash_command, prefix_command, rename = "list")]
+async fn friend_list(ctx: Context<'_>) -> Result<(), Error> {
+    let id = ctx.data().api.get_id_from_discord(ctx.author().id.as_u64()).await?;
+    let friends = ctx.data().api.get_friends(&id).await?;
+    let requests = ctx.data().api.get_friend_requests(&id).await?;
+
+    let mut response = String::from("**Friends:**\n");
+    for friend in &friends {
+        let accountant = if friend.accountant { " (accountant)" } else { "" };
+        writeln!(response, "<@{}>{accountant}", friend.discord_id)?;
+    }
+
+    if !requests.incoming.is_empty() {
+        response.push_str("**Incoming requests:**\n");
+        for request in &requests.incoming {
+            writeln!(response, "<@{}>", request.discord_id)?;
+        }
+    }
+
+    if !requests.outgoing.is_empty() {
+        response.push_str("**Outgoing requests:**\n");
+        for request in &requests.outgoing {
+            writeln!(response, "<@{}>", request.discord_id)?;
+        }
+    }
+
+    ctx.send(|reply| reply.content(response).allowed_mentions(|mentions| mentions.empty_users())).await?;
+    Ok(())
+}
+
+/// Sends a friend request
+#[poise::command(slash_command, prefix_command, rename = "add")]
+async fn friend_add(
+    ctx: Context<'_>,
+    #[description = "Person to befriend"] user: serenity::User,
+) -> Result<(), Error> {
+    let (id, friend) = friend_ids(ctx, &user).await?;
+    ctx.data().api.send_friend_request(&id, &friend).await?;
+    ctx.say(format!("Sent a friend request to {}!", user.name)).await?;
     Ok(())
 }
 
+/// Accepts a friend request
+#[poise::command(slash_command, prefix_command, rename = "accept")]
+async fn friend_accept(
+    ctx: Context<'_>,
+    #[description = "Person who sent the request"] user: serenity::User,
+) -> Result<(), Error> {
+    let (id, friend) = friend_ids(ctx, &user).await?;
+    ctx.data().api.accept_friend_request(&id, &friend).await?;
+    ctx.say(format!("You are now friends with {}!", user.name)).await?;
+    Ok(())
+}
+
+/// Declines a friend request
+#[poise::command(slash_command, prefix_command, rename = "decline")]
+async fn friend_decline(
+    ctx: Context<'_>,
+    #[description = "Person who sent the request"] user: serenity::User,
+) -> Result<(), Error> {
+    let (id, friend) = friend_ids(ctx, &user).await?;
+    ctx.data().api.decline_friend_request(&id, &friend).await?;
+    ctx.say(format!("Declined the friend request from {}", user.name)).await?;
+    Ok(())
+}
+
+/// Removes a friend
+#[poise::command(slash_command, prefix_command, rename = "remove")]
+async fn friend_remove(
+    ctx: Context<'_>,
+    #[description = "Friend to remove"] user: serenity::User,
+) -> Result<(), Error> {
+    let (id, friend) = friend_ids(ctx, &user).await?;
+    ctx.data().api.remove_friend(&id, &friend).await?;
+    ctx.say(format!("Removed {} as a friend", user.name)).await?;
+    Ok(())
+}
+
+/// Allows or disallows a friend to spend from your account
+#[poise::command(slash_command, prefix_command, rename = "accountant")]
+async fn friend_accountant(
+    ctx: Context<'_>,
+    #[description = "Friend to change"] user: serenity::User,
+    #[description = "Whether they can spend from your account"] allowed: bool,
+) -> Result<(), Error> {
+    let (id, friend) = friend_ids(ctx, &user).await?;
+    ctx.data().api.set_friend_accountant(&id, &friend, allowed).await?;
+
+    let response = if allowed {
+        format!("{} can now spend from your account", user.name)
+    } else {
+        format!("{} can no longer spend from your account", user.name)
+    };
+    ctx.say(response).await?;
+    Ok(())
+}
+
+async fn friend_ids(ctx: Context<'_>, user: &serenity::User) -> Result<(Uuid, Uuid), Error> {
+    let id = ctx.data().api.get_id_from_discord(ctx.author().id.as_u64()).await?;
+    let friend = ctx.data().api.get_id_from_discord(user.id.as_u64()).await?;
+    Ok((id, friend))
+}
+
 #[poise::command(prefix_command, hide_in_help)]
 async fn register_commands(ctx: Context<'_>) -> Result<(), Error> {
     poise::builtins::register_application_commands_buttons(ctx).await?;
@@ -70,6 +560,16 @@ You can edit most command messages and the bot will edit its response.",
     Ok(())
 }
 
+/// Reads a comma separated list of discord ids
+fn ids_from_env(name: &str) -> Vec<u64> {
+    env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .filter(|id| !id.trim().is_empty())
+        .map(|id| id.trim().parse().unwrap_or_else(|_| panic!("{name} is not a list of discord ids!")))
+        .collect()
+}
+
 #[tokio::main]
 async fn main() {
     dotenv().expect("A .env file does not exist!");
@@ -79,23 +579,33 @@ async fn main() {
         let client = Client::new();
 
         let website_url = format!("http://{}", env::var("WEBSITE_URL").expect("Could not find discord token from environment variables!"));
+        let api_key = env::var("API_KEY").expect("Could not find api key from environment variables!");
+
+        AekosiaAPI::new(client, &website_url, &api_key)
+    };
 
-        AekosiaAPI::new(client, &website_url)
+    // Nobody can print or burn money unless it is configured
+    let money_admins = MoneyAdmins {
+        roles: ids_from_env("MONEY_ADMIN_ROLES").into_iter().map(serenity::RoleId).collect(),
+        users: ids_from_env("MONEY_ADMIN_USERS").into_iter().map(serenity::UserId).collect(),
+        audit_channel: ids_from_env("MONEY_AUDIT_CHANNEL").first().copied().map(serenity::ChannelId),
     };
 
+    // Newer clippy prefers from_mins for the edit tracker timespan
+    #[allow(clippy::duration_suboptimal_units)]
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
             prefix_options: poise::PrefixFrameworkOptions {
                 prefix: Some(";".into()),
-                edit_tracker: Some(poise::EditTracker::for_timespan(std::time::Duration::from_secs(120))),
+                edit_tracker: Some(poise::EditTracker::for_timespan(Duration::from_secs(120))),
                 ..Default::default()
             },
-            commands: vec![age(), register(), balance(), register_commands(), help()],
+            commands: vec![age(), register(), balance(), pay(), history(), leaderboard(), stats(), money(), friend(), register_commands(), help()],
             ..Default::default()
         })
         .token(env::var("DISCORD_TOKEN").expect("Could not find discord token from environment variables!"))
         .intents(serenity::GatewayIntents::non_privileged().union(serenity::GatewayIntents::MESSAGE_CONTENT))
-        .setup(|_ctx, _ready, _framework| Box::pin(async { Ok(api) }));
+        .setup(|_ctx, _ready, _framework| Box::pin(async { Ok(Data { api, money_admins }) }));
 
     framework.run().await.unwrap();
 }
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE TABLE api_key(
+  id UUID NOT NULL PRIMARY KEY,
+  hash BYTEA NOT NULL UNIQUE,
+  name TEXT NOT NULL,
+  read BOOLEAN NOT NULL DEFAULT FALSE,
+  pay BOOLEAN NOT NULL DEFAULT FALSE,
+  print BOOLEAN NOT NULL DEFAULT FALSE,
+  admin BOOLEAN NOT NULL DEFAULT FALSE,
+  revoked BOOLEAN NOT NULL DEFAULT FALSE
+);
@@ -3,7 +3,8 @@ CREATE TABLE organisation(
   owner UUID NOT NULL,
   public BOOLEAN NOT NULL DEFAULT FALSE,
 
-  CONSTRAINT fk_id FOREIGN KEY(id) REFERENCES account(id)
+  CONSTRAINT fk_id FOREIGN KEY(id) REFERENCES account(id),
+  CONSTRAINT fk_owner FOREIGN KEY(owner) REFERENCES person(id)
 );
 
 CREATE TABLE organisation_member(
@@ -16,6 +17,6 @@ CREATE TABLE organisation_member(
   creation_time TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
 
   PRIMARY KEY(member_id, organisation_id),
-  CONSTRAINT fk_member FOREIGN KEY(member_id) REFERENCES account(id),
+  CONSTRAINT fk_member FOREIGN KEY(member_id) REFERENCES person(id),
   CONSTRAINT fk_organisation FOREIGN KEY(organisation_id) REFERENCES organisation(id)
-);
\ No newline at end of file
+);
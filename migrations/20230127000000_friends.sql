@@ -0,0 +1,19 @@
+CREATE TABLE friend(
+  friend_id UUID NOT NULL,
+  person_id UUID NOT NULL,
+  accountant BOOLEAN NOT NULL DEFAULT FALSE,
+
+  PRIMARY KEY(friend_id, person_id),
+  CONSTRAINT fk_member FOREIGN KEY(friend_id) REFERENCES person(id),
+  CONSTRAINT fk_user FOREIGN KEY(person_id) REFERENCES person(id)
+);
+
+CREATE TABLE friend_request(
+  sender_id UUID NOT NULL,
+  receiver_id UUID NOT NULL,
+  creation_time TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+
+  PRIMARY KEY(sender_id, receiver_id),
+  CONSTRAINT fk_sender FOREIGN KEY(sender_id) REFERENCES person(id),
+  CONSTRAINT fk_receiver FOREIGN KEY(receiver_id) REFERENCES person(id)
+);
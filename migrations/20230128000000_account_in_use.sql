@@ -0,0 +1,10 @@
+ALTER TABLE person
+ADD account_in_use UUID;
+
+UPDATE person SET account_in_use = id;
+
+ALTER TABLE person
+ALTER COLUMN account_in_use SET NOT NULL;
+
+ALTER TABLE person
+ADD CONSTRAINT fk_account_in_use FOREIGN KEY(account_in_use) REFERENCES account(id);
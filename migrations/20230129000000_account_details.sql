@@ -0,0 +1,12 @@
+ALTER TABLE account
+ADD name VARCHAR(32);
+
+UPDATE account SET name = REPLACE(id::TEXT, '-', '');
+
+ALTER TABLE account
+ALTER COLUMN name SET NOT NULL;
+
+ALTER TABLE account
+ADD description TEXT NOT NULL DEFAULT '';
+
+CREATE UNIQUE INDEX account_name_unique ON account (LOWER(name));
@@ -0,0 +1,2 @@
+ALTER TABLE transaction_log
+ADD idempotency_key UUID UNIQUE;
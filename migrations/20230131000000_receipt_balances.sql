@@ -0,0 +1,5 @@
+ALTER TABLE transaction_log
+ADD from_balance BIGINT;
+
+ALTER TABLE transaction_log
+ADD to_balance BIGINT;
@@ -0,0 +1,26 @@
+-- Printed money comes from the mint and removed money goes to the burn account,
+-- so every balance is backed by the transaction log
+INSERT INTO account (id, name, description) VALUES
+  ('00000000-0000-7000-8000-000000000001', 'Mint', 'Printed money comes from this account'),
+  ('00000000-0000-7000-8000-000000000002', 'Burn', 'Money taken out of circulation goes to this account');
+
+ALTER TABLE transaction_log
+ADD memo TEXT;
+
+-- Money printed before this migration left no trace, so it's recorded as opening balances.
+-- Each one reuses the v7 id of its account, dating it to when the account was created and
+-- so before anything the account paid or received. The balances after it get replayed later.
+INSERT INTO transaction_log (id, from_id, to_id, amount, memo)
+SELECT drift.id, '00000000-0000-7000-8000-000000000001', drift.id, drift.amount, 'Opening balance'
+FROM (
+  SELECT account.id, account.balance
+    - COALESCE((SELECT SUM(amount) FROM transaction_log WHERE to_id = account.id), 0)
+    + COALESCE((SELECT SUM(amount) FROM transaction_log WHERE from_id = account.id), 0) AS amount
+  FROM account
+  WHERE account.id NOT IN ('00000000-0000-7000-8000-000000000001', '00000000-0000-7000-8000-000000000002')
+) AS drift
+WHERE drift.amount != 0;
+
+UPDATE account
+SET balance = -(SELECT COALESCE(SUM(amount), 0) FROM transaction_log WHERE from_id = '00000000-0000-7000-8000-000000000001')
+WHERE id = '00000000-0000-7000-8000-000000000001';
@@ -0,0 +1,70 @@
+-- Every transaction is made of postings, each crediting (positive) or debiting (negative)
+-- one account, and the postings of a transaction always sum to zero
+CREATE TABLE ledger_transaction(
+  id UUID NOT NULL PRIMARY KEY,
+  memo TEXT,
+  idempotency_key UUID UNIQUE
+);
+
+CREATE TABLE posting(
+  transaction_id UUID NOT NULL,
+  line SMALLINT NOT NULL,
+  account_id UUID NOT NULL,
+  amount BIGINT NOT NULL,
+  -- Balance of the account right after this posting
+  balance BIGINT NOT NULL,
+
+  PRIMARY KEY(transaction_id, line),
+  CONSTRAINT fk_transaction FOREIGN KEY(transaction_id) REFERENCES ledger_transaction(id),
+  CONSTRAINT fk_account FOREIGN KEY(account_id) REFERENCES account(id)
+);
+
+CREATE INDEX posting_account ON posting (account_id, transaction_id);
+
+INSERT INTO ledger_transaction (id, memo, idempotency_key)
+SELECT id, memo, idempotency_key FROM transaction_log;
+
+-- Payments from before receipts existed have no recorded balances, so they get replayed
+INSERT INTO posting (transaction_id, line, account_id, amount, balance)
+SELECT id, line, account_id, amount, COALESCE(balance, SUM(amount) OVER (PARTITION BY account_id ORDER BY id, line))
+FROM (
+  SELECT id, 0 AS line, from_id AS account_id, -amount AS amount, from_balance AS balance FROM transaction_log
+  UNION ALL
+  SELECT id, 1 AS line, to_id AS account_id, amount, to_balance AS balance FROM transaction_log
+) AS lines;
+
+DROP TABLE transaction_log;
+
+-- Balances are a cache of the postings, kept in sync here so they can't drift
+CREATE FUNCTION apply_posting() RETURNS TRIGGER AS $$
+BEGIN
+  UPDATE account SET balance = balance + NEW.amount WHERE id = NEW.account_id RETURNING balance INTO NEW.balance;
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER posting_apply BEFORE INSERT ON posting
+FOR EACH ROW EXECUTE FUNCTION apply_posting();
+
+CREATE FUNCTION reject_posting_change() RETURNS TRIGGER AS $$
+BEGIN
+  RAISE EXCEPTION 'Postings cannot be changed, record a new transaction instead';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER posting_immutable BEFORE UPDATE OR DELETE ON posting
+FOR EACH ROW EXECUTE FUNCTION reject_posting_change();
+
+CREATE FUNCTION check_transaction_balanced() RETURNS TRIGGER AS $$
+BEGIN
+  IF (SELECT SUM(amount) FROM posting WHERE transaction_id = NEW.transaction_id) != 0 THEN
+    RAISE EXCEPTION 'Postings of transaction % do not sum to zero', NEW.transaction_id;
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Checked when the database transaction commits, after all postings are inserted
+CREATE CONSTRAINT TRIGGER posting_balanced AFTER INSERT ON posting
+DEFERRABLE INITIALLY DEFERRED
+FOR EACH ROW EXECUTE FUNCTION check_transaction_balanced();
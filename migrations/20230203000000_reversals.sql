@@ -0,0 +1,3 @@
+-- A transaction can only be reversed once, partially or in full
+ALTER TABLE ledger_transaction ADD COLUMN reverses UUID UNIQUE;
+ALTER TABLE ledger_transaction ADD CONSTRAINT fk_reverses FOREIGN KEY(reverses) REFERENCES ledger_transaction(id);
@@ -0,0 +1,10 @@
+-- Transactions that skipped the usual checks through an admin override
+CREATE TABLE admin_override(
+  transaction_id UUID NOT NULL PRIMARY KEY,
+  api_key_id UUID NOT NULL,
+  actor UUID NOT NULL,
+  reason TEXT NOT NULL,
+
+  CONSTRAINT fk_transaction FOREIGN KEY(transaction_id) REFERENCES ledger_transaction(id),
+  CONSTRAINT fk_api_key FOREIGN KEY(api_key_id) REFERENCES api_key(id)
+);
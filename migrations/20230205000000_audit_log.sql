@@ -0,0 +1,16 @@
+-- Every request that could change state, successful or not
+CREATE TABLE audit_log(
+  id UUID NOT NULL PRIMARY KEY,
+  api_key_id UUID NOT NULL,
+  -- The HTTP method and route, like `POST /eco/payment`
+  action TEXT NOT NULL,
+  actor UUID,
+  target UUID,
+  parameters JSONB NOT NULL,
+  status SMALLINT NOT NULL,
+
+  CONSTRAINT fk_api_key FOREIGN KEY(api_key_id) REFERENCES api_key(id)
+);
+
+CREATE INDEX audit_log_actor ON audit_log (actor, id);
+CREATE INDEX audit_log_target ON audit_log (target, id);
@@ -0,0 +1,4 @@
+-- Free form tag like `rent` or `wages`, stored lowercase
+ALTER TABLE ledger_transaction ADD COLUMN category VARCHAR(32);
+
+CREATE INDEX ledger_transaction_category ON ledger_transaction (category);
@@ -0,0 +1,5 @@
+-- A transaction can be reversed more than once, as long as the reversals
+-- don't send back more than the original amount in total
+ALTER TABLE ledger_transaction DROP CONSTRAINT ledger_transaction_reverses_key;
+
+CREATE INDEX ledger_transaction_reverses ON ledger_transaction (reverses);
@@ -1,5 +0,0 @@
-ALTER TABLE account
-ADD name VARCHAR(32) NOT NULL;
-
-ALTER TABLE account
-ADD description TEXT NOT NULL DEFAULT '';
\ No newline at end of file
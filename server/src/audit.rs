@@ -0,0 +1,72 @@
+use axum::{
+    extract::{MatchedPath, Path, Query, State},
+    http::{Method, Request},
+    middleware::Next,
+    response::Response,
+};
+use sqlx::{types::Json, PgPool};
+use std::collections::BTreeMap;
+use tracing::{event, Level};
+use uuid::Uuid;
+
+use crate::auth::ApiKey;
+
+/// Records every request that could change state in the audit log, along with its response status.
+/// Has to run after authentication and as a route layer so the matched route is known.
+pub async fn record<B>(
+    State(db): State<PgPool>,
+    key: Option<ApiKey>,
+    matched_path: Option<MatchedPath>,
+    path: Option<Path<BTreeMap<String, String>>>,
+    query: Option<Query<BTreeMap<String, String>>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let method = request.method().clone();
+
+    if method == Method::GET || method == Method::HEAD {
+        return next.run(request).await;
+    }
+
+    let response = next.run(request).await;
+
+    let Some(key) = key else {
+        return response;
+    };
+
+    let query = query.map(|Query(query)| query).unwrap_or_default();
+    let path = path.map(|Path(path)| path).unwrap_or_default();
+    let action = format!("{method} {}", matched_path.as_ref().map_or("", MatchedPath::as_str));
+    let actor = uuid_param(&query, &["actor", "owner"]);
+    let target = uuid_param(&path, &["id"]).or_else(|| uuid_param(&query, &["to"]));
+
+    let mut parameters = query;
+    parameters.extend(path);
+
+    let result = sqlx::query!(
+        "INSERT INTO audit_log (id, api_key_id, action, actor, target, parameters, status) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        Uuid::now_v7(),
+        key.id,
+        action,
+        actor,
+        target,
+        Json(parameters) as _,
+        i16::try_from(response.status().as_u16()).unwrap_or_default()
+    )
+    .execute(&db)
+    .await;
+
+    // The request already happened, failing to audit it should not hide its response
+    if let Err(err) = result {
+        event!(Level::ERROR, %err, action, "Failed to record audit log entry");
+    }
+
+    response
+}
+
+/// The first of `names` that holds a uuid
+fn uuid_param(params: &BTreeMap<String, String>, names: &[&str]) -> Option<Uuid> {
+    names
+        .iter()
+        .find_map(|name| params.get(*name).and_then(|value| value.parse().ok()))
+}
@@ -0,0 +1,120 @@
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, State},
+    http::{header::AUTHORIZATION, request::Parts, Request},
+    middleware::Next,
+    response::Response,
+};
+use sha2::{Digest, Sha256};
+use shared::error::ErrorCode;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::Error;
+use crate::error::Result;
+
+/// The api key that authenticated the current request
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub read: bool,
+    pub pay: bool,
+    pub print: bool,
+    pub admin: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Scope {
+    Read,
+    Pay,
+    /// Printing and burning, everything that changes the money supply
+    Print,
+    Admin,
+}
+
+impl ApiKey {
+    /// Admin keys are allowed to do everything
+    #[must_use]
+    pub fn has(&self, scope: Scope) -> bool {
+        self.admin
+            || match scope {
+                Scope::Read => self.read,
+                Scope::Pay => self.pay,
+                Scope::Print => self.print,
+                Scope::Admin => false,
+            }
+    }
+
+    pub fn require(&self, scope: Scope) -> Result<()> {
+        if self.has(scope) {
+            return Ok(());
+        }
+
+        Err(Error::HttpError(ErrorCode::Forbidden, "This api key lacks the scope for this action"))
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ApiKey {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        parts
+            .extensions
+            .get::<ApiKey>()
+            .cloned()
+            .ok_or(Error::HttpError(ErrorCode::Unauthorized, "Missing api key"))
+    }
+}
+
+#[must_use]
+pub fn hash_key(key: &str) -> Vec<u8> {
+    Sha256::digest(key.as_bytes()).to_vec()
+}
+
+#[must_use]
+pub fn generate_key() -> String {
+    format!("{}{}", Uuid::new_v4().as_simple(), Uuid::new_v4().as_simple())
+}
+
+/// Rejects any request without a valid `Authorization: Bearer <key>` header
+pub async fn authenticate<B>(
+    State(db): State<PgPool>,
+    mut request: Request<B>,
+    next: Next<B>,
+) -> Result<Response> {
+    let key = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .ok_or(Error::HttpError(ErrorCode::Unauthorized, "Missing api key"))?;
+
+    let api_key = sqlx::query_as!(
+        ApiKey,
+        "SELECT id, name, read, pay, print, admin FROM api_key WHERE hash = $1 AND NOT revoked",
+        hash_key(key)
+    )
+    .fetch_optional(&db)
+    .await?
+    .ok_or(Error::HttpError(ErrorCode::Unauthorized, "Invalid api key"))?;
+
+    request.extensions_mut().insert(api_key);
+
+    Ok(next.run(request).await)
+}
+
+/// Makes sure the key from `ROOT_API_KEY` exists so a fresh database can be administrated
+pub async fn ensure_root_key(key: &str, db: &PgPool) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO api_key (id, hash, name, read, pay, print, admin) VALUES ($1, $2, 'root', TRUE, TRUE, TRUE, TRUE) ON CONFLICT (hash) DO NOTHING",
+        Uuid::now_v7(),
+        hash_key(key)
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
@@ -1,3 +1,6 @@
+// The sqlx 0.6 derive checks for `feature = "postgres"` in this crate, which newer compilers flag
+#![allow(unexpected_cfgs)]
+
 use serde::{Serialize, Serializer};
 use std::convert::Into;
 use std::fmt;
@@ -3,8 +3,10 @@
 
 use axum::{
     http::StatusCode,
-    response::{IntoResponse, Response}
+    response::{IntoResponse, Response},
+    Json
 };
+use shared::error::{ErrorBody, ErrorCode};
 use thiserror::Error;
 use tracing::{event, span, Level};
 
@@ -23,10 +25,10 @@ pub enum Error {
     UUID(#[from] UUIDError),
 
     #[error("A error for returning a alternate http response")]
-    HttpError(StatusCode, &'static str),
+    HttpError(ErrorCode, &'static str),
 
-    #[error("A unique value already existed: `{0}`")]
-    AlreadyExists(&'static str),
+    #[error("A unique value already existed: `{1}`")]
+    AlreadyExists(ErrorCode, &'static str),
 
     #[error("Failed at finding this resource: `{0}`")]
     NotFound(&'static str),
@@ -36,38 +38,38 @@ pub enum Error {
 }
 
 pub trait UniqueValueError<T> {
-    fn or_already_exists(self, value: &'static str) -> Result<T>;
+    fn or_already_exists(self, code: ErrorCode, value: &'static str) -> Result<T>;
 }
 
 impl<T> UniqueValueError<T> for Result<T> {
-    fn or_already_exists(self, value_name: &'static str) -> Result<T> {
+    fn or_already_exists(self, code: ErrorCode, value_name: &'static str) -> Result<T> {
         let err = match self {
             Ok(res) => return Ok(res),
             Err(Error::Database(db_error)) => db_error,
             Err(err) => return Err(err),
         };
 
-        to_unique_error!(err, value_name)
+        to_unique_error!(err, code, value_name)
     }
 }
 
 impl<T> UniqueValueError<T> for StdResult<T, SQLxError> {
-    fn or_already_exists(self, value_name: &'static str) -> Result<T> {
+    fn or_already_exists(self, code: ErrorCode, value_name: &'static str) -> Result<T> {
         let err = match self {
             Ok(res) => return Ok(res),
             Err(err) => err,
         };
 
-        to_unique_error!(err, value_name)
+        to_unique_error!(err, code, value_name)
     }
 }
 
 macro_rules! to_unique_error {
-    ($err:tt, $value_name:tt) => { 
+    ($err:tt, $code:tt, $value_name:tt) => {
         {
             if let Some(code) = $err.as_database_error().and_then(DatabaseError::code) {
                 if code == "23505" {
-                    return Err(Error::AlreadyExists($value_name));
+                    return Err(Error::AlreadyExists($code, $value_name));
                 }
             }
             return Err($err.into());    
@@ -77,22 +79,42 @@ macro_rules! to_unique_error {
 
 pub(crate) use to_unique_error;
 
+/// Status of the response for each error code
+fn status(code: ErrorCode) -> StatusCode {
+    match code {
+        ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+        ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+        ErrorCode::NotFound => StatusCode::NOT_FOUND,
+        ErrorCode::IdempotencyConflict => StatusCode::CONFLICT,
+        ErrorCode::InsufficientFunds
+        | ErrorCode::InvalidAmount
+        | ErrorCode::InvalidInput
+        | ErrorCode::InvalidOperation
+        | ErrorCode::AlreadyExists
+        | ErrorCode::AlreadyRegistered
+        | ErrorCode::AlreadyReversed
+        | ErrorCode::NameTaken => StatusCode::BAD_REQUEST,
+        ErrorCode::Internal | ErrorCode::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn error_response(code: ErrorCode, message: String) -> Response {
+    (status(code), Json(ErrorBody { code, message })).into_response()
+}
+
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
         let span = span!(Level::DEBUG, "error_handling");
         let _entered = span.enter();
 
         match self {
-            Error::UUID(error) =>  (StatusCode::BAD_REQUEST, error.to_string()).into_response(),
-            Error::NotFound(resource) => {
-                (StatusCode::NOT_FOUND, format!("Could not find {resource}")).into_response()
-            }
-            Error::AlreadyExists(error) => (StatusCode::BAD_REQUEST, error).into_response(),
-            Error::HttpError(status_code, msg) => (status_code, msg).into_response(),
+            Error::UUID(error) => error_response(ErrorCode::InvalidInput, error.to_string()),
+            Error::NotFound(resource) => error_response(ErrorCode::NotFound, format!("Could not find {resource}")),
+            Error::AlreadyExists(code, msg) | Error::HttpError(code, msg) => error_response(code, msg.to_string()),
             _ => {
                 event!(Level::ERROR, %self, "Received unexpected error");
-                (StatusCode::INTERNAL_SERVER_ERROR, "An unexpected error has occurred").into_response()
+                error_response(ErrorCode::Internal, "An unexpected error has occurred".to_string())
             }
         }
     }
-}
\ No newline at end of file
+}
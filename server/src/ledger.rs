@@ -0,0 +1,72 @@
+use shared::error::ErrorCode;
+use shared::types::Posting;
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::error::UniqueValueError;
+
+/// Locks the accounts until the transaction ends and returns their balances, accounts that don't exist
+/// are left out. Rows are always locked in id order so concurrent transactions can't deadlock,
+/// balance checks have to use these balances to be safe against concurrent payments.
+pub async fn lock_balances(accounts: &[Uuid], tx: &mut Transaction<'_, Postgres>) -> Result<HashMap<Uuid, i64>> {
+    let balances = sqlx::query!(
+        "SELECT id, balance FROM account WHERE id = ANY($1) ORDER BY id FOR UPDATE",
+        accounts
+    )
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .map(|row| (row.id, row.balance))
+    .collect();
+
+    Ok(balances)
+}
+
+/// Records a transaction made of `postings`, returning its id and the balance of each account
+/// right after its posting. The database rejects the transaction on commit if the postings
+/// don't sum to zero, the caller is responsible for any other checks.
+pub async fn record(
+    postings: &[Posting],
+    memo: Option<&str>,
+    category: Option<&str>,
+    idempotency_key: Option<Uuid>,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<(Uuid, Vec<i64>)> {
+    let accounts: Vec<Uuid> = postings.iter().map(|posting| posting.account).collect();
+    lock_balances(&accounts, tx).await?;
+
+    let transaction_uuid = Uuid::now_v7();
+
+    sqlx::query!(
+        "INSERT INTO ledger_transaction (id, memo, category, idempotency_key) VALUES ($1, $2, $3, $4)",
+        transaction_uuid,
+        memo,
+        category,
+        idempotency_key
+    )
+    .execute(&mut *tx)
+    .await
+    .or_already_exists(ErrorCode::IdempotencyConflict, "idempotency key")?;
+
+    let mut balances = Vec::with_capacity(postings.len());
+
+    for (line, posting) in (0_i16..).zip(postings) {
+        // The balance gets filled in by the trigger that applies the posting to the account
+        let balance = sqlx::query!(
+            "INSERT INTO posting (transaction_id, line, account_id, amount) VALUES ($1, $2, $3, $4) RETURNING balance",
+            transaction_uuid,
+            line,
+            posting.account,
+            posting.amount
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .balance;
+
+        balances.push(balance);
+    }
+
+    Ok((transaction_uuid, balances))
+}
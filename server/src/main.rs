@@ -3,10 +3,11 @@
 
 use axum::{
     routing::get,
-    extract::State,
+    middleware,
     Router,
 };
 use std::net::SocketAddr;
+use std::time::Duration;
 use dotenvy::dotenv;
 use std::env;
 use sqlx::{postgres::PgPoolOptions, PgPool};
@@ -17,11 +18,18 @@ use crate::routes::*;
 use crate::error::Error;
 use crate::error::Result;
 
+pub mod audit;
+pub mod auth;
 pub mod discord_id;
 pub mod error;
+pub mod ledger;
 pub mod routes;
 pub mod timestamp;
 pub mod payment;
+pub mod reconcile;
+pub mod validate;
+
+const DEFAULT_RECONCILE_INTERVAL_SECS: u64 = 60 * 60;
 
 #[tokio::main]
 pub async fn main() {
@@ -34,7 +42,36 @@ pub async fn main() {
         .await
         .expect("Failed to connect to database");
 
-    let app = routes().with_state(pool);
+    if let Ok(root_key) = env::var("ROOT_API_KEY") {
+        auth::ensure_root_key(&root_key, &pool)
+            .await
+            .expect("Failed to create the root api key");
+    }
+
+    let mut args = env::args().skip(1);
+    if let Some(command) = args.next() {
+        match command.as_str() {
+            "reconcile" => {
+                let repair = args.any(|arg| arg == "--repair");
+                let report = reconcile::reconcile(repair, &pool)
+                    .await
+                    .expect("Failed to reconcile the ledger");
+                println!("{}", serde_json::to_string_pretty(&report).unwrap());
+            }
+            _ => eprintln!("Unknown command `{command}`, available commands: reconcile [--repair]"),
+        }
+
+        return;
+    }
+
+    let reconcile_interval = env::var("RECONCILE_INTERVAL_SECS")
+        .map_or(DEFAULT_RECONCILE_INTERVAL_SECS, |secs| secs.parse().expect("RECONCILE_INTERVAL_SECS is not a number!"));
+    tokio::spawn(reconcile::run_scheduled(Duration::from_secs(reconcile_interval), pool.clone()));
+
+    let app = routes()
+        .route_layer(middleware::from_fn_with_state(pool.clone(), audit::record))
+        .layer(middleware::from_fn_with_state(pool.clone(), auth::authenticate))
+        .with_state(pool);
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 8080)); //127.0.0.1:8080
     tracing::debug!("listening on {}", addr);
@@ -50,15 +87,14 @@ fn routes() -> Router<PgPool> {
             .route("/yeet_error", get(yeet_error))
             .nest("/person", person::routes())
             .nest("/eco", eco::routes())
+            .nest("/account", account::routes())
+            .nest("/org", org::routes())
+            .nest("/admin", admin::routes())
 }
 
-async fn index(State(db): State<PgPool>) -> Result<String> {
-    let result = sqlx::query!("SELECT COUNT(*) as \"count!\" FROM account")
-        .fetch_one(&db)
-        .await?
-        .count;
-
-    Ok(format!("Hello, world! Accounts: {result}"))
+#[allow(clippy::unused_async)]
+async fn index() -> &'static str {
+    "Hello, world! Statistics about the economy are at /eco/stats"
 }
 
 #[allow(clippy::unused_async)]
@@ -1,65 +1,404 @@
-use axum::http::StatusCode;
+use shared::error::ErrorCode;
+use shared::types::{PaymentParams, Posting, Receipt, ReverseParams, BURN_ACCOUNT, MINT_ACCOUNT};
 use uuid::Uuid;
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
 use crate::error::Result;
 use crate::error::Error;
+use crate::ledger;
+use crate::validate::{validate_category, validate_reason};
+use crate::timestamp::UUIDTime;
 
-pub async fn payment(
-    to: Uuid,
-    from: Uuid,
-    amount: i64,
-    force: Option<bool>,
-    db: &PgPool,
-) -> Result<()> {
-    if force != Option::Some(true) {
-        #[allow(clippy::comparison_chain)]
-        if amount == 0 {
-            return Err(Error::HttpError(StatusCode::FORBIDDEN, "Cannot send zero money!"));
-        } else if amount < 0 {
-            return Err(Error::HttpError(StatusCode::FORBIDDEN, "You cannot pay negative money"));
-        }
+/// Checks that `actor` is a person allowed to spend from `account`, either by owning it,
+/// by being a accountant of the organisation behind it or by being a friend marked as accountant
+pub async fn authorize_spend(actor: Uuid, account: Uuid, db: impl PgExecutor<'_>) -> Result<()> {
+    let authorization = sqlx::query!(
+        r###"SELECT EXISTS(SELECT 1 FROM person WHERE id = $2) as "person!", $1 = $2 OR EXISTS(
+            SELECT 1 FROM organisation WHERE id = $1 AND owner = $2
+            UNION ALL
+            SELECT 1 FROM organisation_member WHERE organisation_id = $1 AND member_id = $2 AND accountant
+            UNION ALL
+            SELECT 1 FROM friend WHERE person_id = $1 AND friend_id = $2 AND accountant
+        ) as "allowed!""###,
+        account,
+        actor
+    )
+    .fetch_one(db)
+    .await?;
+
+    // Accounts of organisations would otherwise be able to act as themselves
+    if !authorization.person {
+        return Err(Error::NotFound("person"));
     }
 
-    let mut tx = db.begin().await?;
+    if !authorization.allowed {
+        return Err(Error::HttpError(ErrorCode::Forbidden, "You are not allowed to spend from this account"));
+    }
 
-    let balance = sqlx::query!("SELECT balance FROM account WHERE id = $1", from)
-        .fetch_one(&mut tx)
+    Ok(())
+}
+
+/// The account a person currently acts with when no account is given
+pub async fn account_in_use(person: Uuid, db: impl PgExecutor<'_>) -> Result<Uuid> {
+    let account = sqlx::query!("SELECT account_in_use FROM person WHERE id = $1", person)
+        .fetch_optional(db)
         .await?
-        .balance;
+        .ok_or(Error::NotFound("person"))?
+        .account_in_use;
+
+    Ok(account)
+}
+
+/// Whether `account` is the mint or burn account, which only printing and burning move money through
+#[must_use]
+pub fn is_system_account(account: Uuid) -> bool {
+    account == MINT_ACCOUNT || account == BURN_ACCOUNT
+}
 
-    if balance < amount {
-        return Err(Error::HttpError(StatusCode::BAD_REQUEST, "You lack the funds to send this payment"));
+/// Money only enters and leaves the mint and burn accounts by printing and burning it,
+/// anything else touching them needs an admin override
+fn reject_system_accounts(from: Uuid, to: Uuid, admin_override: Option<&Override<'_>>) -> Result<()> {
+    if admin_override.is_none() && (is_system_account(from) || is_system_account(to)) {
+        return Err(Error::HttpError(ErrorCode::Forbidden, "Money can only be moved in and out of the mint and burn accounts by printing and burning it"));
+    }
+
+    Ok(())
+}
+
+/// Lifts the checks on a transaction, recorded along with who did it and why.
+/// The caller is responsible for making sure only admins can override.
+pub struct Override<'a> {
+    pub api_key: Uuid,
+    pub reason: &'a str,
+}
+
+/// Executes a payment, returning its receipt. Repeating a payment
+/// with the same idempotency key returns the original receipt instead.
+pub async fn payment(params: &PaymentParams, admin_override: Option<&Override<'_>>, db: &PgPool) -> Result<Receipt> {
+    let amount = params.amount;
+
+    if amount == 0 {
+        return Err(Error::HttpError(ErrorCode::InvalidAmount, "Cannot send zero money!"));
+    } else if amount < 0 && admin_override.is_none() {
+        return Err(Error::HttpError(ErrorCode::InvalidAmount, "You cannot pay negative money"));
+    }
+
+    let memo = params.memo.as_deref().map(validate_reason).transpose()?;
+    let category = params.category.as_deref().map(validate_category).transpose()?;
+
+    let mut tx = db.begin().await?;
+
+    let from = match params.from {
+        Some(from) => from,
+        None => account_in_use(params.actor, &mut tx).await?,
     };
+    let to = params.to;
 
-    sqlx::query!(
-        "UPDATE account SET balance = balance - $1 WHERE id = $2",
-        amount,
-        from
+    reject_system_accounts(from, to, admin_override)?;
+
+    let balances = ledger::lock_balances(&[from, to], &mut tx).await?;
+
+    let balance = *balances.get(&from).ok_or(Error::NotFound("sender account"))?;
+
+    if !balances.contains_key(&to) {
+        return Err(Error::NotFound("receiver account"));
+    }
+
+    authorize_spend(params.actor, from, &mut tx).await?;
+
+    if let Some(key) = params.idempotency_key {
+        if let Some(receipt) = original_receipt(key, from, to, amount, memo, category.as_deref(), &mut tx).await? {
+            return Ok(receipt);
+        }
+    }
+
+    if admin_override.is_none() && balance < amount {
+        return Err(Error::HttpError(ErrorCode::InsufficientFunds, "You lack the funds to send this payment"));
+    }
+
+    let receipt = match (
+        transfer(from, to, amount, memo, category.as_deref(), params.idempotency_key, &mut tx).await,
+        params.idempotency_key,
+    ) {
+        // A concurrent retry with the same key got to commit first
+        (Err(Error::AlreadyExists(ErrorCode::IdempotencyConflict, _)), Some(key)) => {
+            drop(tx);
+
+            return original_receipt(key, from, to, amount, memo, category.as_deref(), db)
+                .await?
+                .ok_or(Error::AlreadyExists(ErrorCode::IdempotencyConflict, "idempotency key"));
+        }
+        (receipt, _) => receipt?,
+    };
+
+    if let Some(admin_override) = admin_override {
+        record_override(receipt.id, params.actor, admin_override, &mut tx).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(receipt)
+}
+
+/// Sends money of a payment back, fully or partially, as a new transaction linked to the original.
+/// Partial reversals can be followed by more until the whole amount is sent back.
+/// An admin override skips the permission and balance checks.
+pub async fn reverse(
+    id: Uuid,
+    params: &ReverseParams,
+    reason: Option<&str>,
+    admin_override: Option<&Override<'_>>,
+    db: &PgPool,
+) -> Result<Receipt> {
+    let mut tx = db.begin().await?;
+
+    let original = sqlx::query!(
+        r###"SELECT ledger_transaction.reverses, sender.account_id as from_id, receiver.account_id as to_id, receiver.amount,
+            (SELECT COUNT(*) FROM posting WHERE transaction_id = ledger_transaction.id) as "postings!",
+            (SELECT COALESCE(SUM(posting.amount), 0) FROM ledger_transaction reversal
+                INNER JOIN posting ON posting.transaction_id = reversal.id AND posting.line = 1
+                WHERE reversal.reverses = ledger_transaction.id)::BIGINT as "reversed!"
+        FROM ledger_transaction
+        INNER JOIN posting sender ON sender.transaction_id = ledger_transaction.id AND sender.line = 0
+        INNER JOIN posting receiver ON receiver.transaction_id = ledger_transaction.id AND receiver.line = 1
+        WHERE ledger_transaction.id = $1
+        FOR UPDATE OF ledger_transaction"###,
+        id
     )
-    .execute(&mut tx)
-    .await?;
+    .fetch_optional(&mut tx)
+    .await?
+    .ok_or(Error::NotFound("transaction"))?;
+
+    if original.postings != 2 {
+        return Err(Error::HttpError(ErrorCode::InvalidOperation, "Only payments between two accounts can be reversed"));
+    }
+
+    if original.reverses.is_some() {
+        return Err(Error::HttpError(ErrorCode::InvalidOperation, "Reversals cannot be reversed"));
+    }
+
+    // Locking the original above keeps concurrent reversals from sending back too much
+    let remaining = original.amount - original.reversed;
+
+    if remaining <= 0 {
+        return Err(Error::AlreadyExists(ErrorCode::AlreadyReversed, "This transaction was already reversed!"));
+    }
+
+    let amount = params.amount.unwrap_or(remaining);
+
+    if amount <= 0 || amount > remaining {
+        return Err(Error::HttpError(ErrorCode::InvalidAmount, "You can only reverse up to what is left of the original transaction"));
+    }
+
+    // The money goes back the way it came
+    let (from, to) = (original.to_id, original.from_id);
+
+    reject_system_accounts(from, to, admin_override)?;
+
+    if admin_override.is_none() {
+        authorize_spend(params.actor, from, &mut tx).await?;
+
+        let balance = *ledger::lock_balances(&[from, to], &mut tx)
+            .await?
+            .get(&from)
+            .ok_or(Error::NotFound("sender account"))?;
+
+        if balance < amount {
+            return Err(Error::HttpError(ErrorCode::InsufficientFunds, "You lack the funds to reverse this transaction"));
+        }
+    }
+
+    let receipt = transfer(from, to, amount, reason, None, None, &mut tx).await?;
+
+    sqlx::query!("UPDATE ledger_transaction SET reverses = $2 WHERE id = $1", receipt.id, id)
+        .execute(&mut tx)
+        .await?;
+
+    if let Some(admin_override) = admin_override {
+        record_override(receipt.id, params.actor, admin_override, &mut tx).await?;
+    }
 
+    tx.commit().await?;
+
+    Ok(receipt)
+}
+
+async fn record_override(
+    transaction: Uuid,
+    actor: Uuid,
+    admin_override: &Override<'_>,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<()> {
     sqlx::query!(
-        "UPDATE account SET balance = balance + $1 WHERE id = $2",
-        amount,
-        to
+        "INSERT INTO admin_override (transaction_id, api_key_id, actor, reason) VALUES ($1, $2, $3, $4)",
+        transaction,
+        admin_override.api_key,
+        actor,
+        admin_override.reason
     )
-    .execute(&mut tx)
+    .execute(&mut *tx)
     .await?;
 
-    let transaction_uuid = Uuid::now_v7();
+    Ok(())
+}
 
-    sqlx::query!(
-        "INSERT INTO transaction_log (id, from_id, to_id, amount) VALUES ($1, $2, $3, $4)",
-        transaction_uuid,
+/// Moves money between two accounts and records it in the ledger, the caller is responsible for any checks
+pub async fn transfer(
+    from: Uuid,
+    to: Uuid,
+    amount: i64,
+    memo: Option<&str>,
+    category: Option<&str>,
+    idempotency_key: Option<Uuid>,
+    tx: &mut Transaction<'_, Postgres>,
+) -> Result<Receipt> {
+    let postings = [
+        Posting { account: from, amount: -amount },
+        Posting { account: to, amount },
+    ];
+
+    let (transaction_uuid, balances) = ledger::record(&postings, memo, category, idempotency_key, tx).await?;
+
+    Ok(Receipt {
+        id: transaction_uuid,
         from,
         to,
-        amount
+        amount,
+        from_balance: balances[0],
+        to_balance: balances[1],
+        time: UUIDTime::from(transaction_uuid).to_unix_sec(),
+    })
+}
+
+/// Finds the receipt of a payment made earlier with the same idempotency key,
+/// which has to match the payment being retried in every detail
+async fn original_receipt(
+    idempotency_key: Uuid,
+    from: Uuid,
+    to: Uuid,
+    amount: i64,
+    memo: Option<&str>,
+    category: Option<&str>,
+    db: impl PgExecutor<'_>,
+) -> Result<Option<Receipt>> {
+    // Payments are always recorded with the sender on the first line and the receiver on the second
+    let Some(original) = sqlx::query!(
+        r###"SELECT ledger_transaction.id, ledger_transaction.memo, ledger_transaction.category, sender.account_id as from_id, receiver.account_id as to_id,
+            receiver.amount, sender.balance as from_balance, receiver.balance as to_balance
+        FROM ledger_transaction
+        INNER JOIN posting sender ON sender.transaction_id = ledger_transaction.id AND sender.line = 0
+        INNER JOIN posting receiver ON receiver.transaction_id = ledger_transaction.id AND receiver.line = 1
+        WHERE idempotency_key = $1"###,
+        idempotency_key
     )
-    .execute(&mut tx)
-    .await?;
+    .fetch_optional(db)
+    .await? else {
+        return Ok(None);
+    };
 
-    tx.commit().await?;
+    if original.from_id != from
+        || original.to_id != to
+        || original.amount != amount
+        || original.memo.as_deref() != memo
+        || original.category.as_deref() != category
+    {
+        return Err(Error::HttpError(ErrorCode::IdempotencyConflict, "This idempotency key was already used for a different payment"));
+    }
 
-    Ok(())
-}
\ No newline at end of file
+    Ok(Some(Receipt {
+        id: original.id,
+        from: original.from_id,
+        to: original.to_id,
+        amount: original.amount,
+        from_balance: original.from_balance,
+        to_balance: original.to_balance,
+        time: UUIDTime::from(original.id).to_unix_sec(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::types::MINT_ACCOUNT;
+    use sqlx::postgres::PgPoolOptions;
+
+    const PARALLEL_PAYMENTS: usize = 50;
+    const AFFORDABLE_PAYMENTS: i64 = 10;
+
+    async fn create_account(db: &PgPool) -> Uuid {
+        let id = Uuid::now_v7();
+
+        sqlx::query("INSERT INTO account (id, name) VALUES ($1, $2)")
+            .bind(id)
+            .bind(id.as_simple().to_string())
+            .execute(db)
+            .await
+            .unwrap();
+
+        id
+    }
+
+    /// Only people can spend, so the paying account needs a person behind it
+    async fn create_person(db: &PgPool) -> Uuid {
+        let id = create_account(db).await;
+
+        sqlx::query("INSERT INTO person (id, discord_id, account_in_use) VALUES ($1, $2, $1)")
+            .bind(id)
+            .bind(&id.as_bytes()[8..])
+            .execute(db)
+            .await
+            .unwrap();
+
+        id
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    #[ignore = "Needs a local Postgres with the migrations applied in DATABASE_URL"]
+    async fn parallel_payments_never_overdraw() {
+        let db = PgPoolOptions::new()
+            .max_connections(20)
+            .connect(&std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+
+        let from = create_person(&db).await;
+        let to = create_account(&db).await;
+
+        let mut tx = db.begin().await.unwrap();
+        transfer(MINT_ACCOUNT, from, AFFORDABLE_PAYMENTS, Some("Stress test"), None, None, &mut tx).await.unwrap();
+        tx.commit().await.unwrap();
+
+        let payments = (0..PARALLEL_PAYMENTS).map(|_| {
+            let db = db.clone();
+
+            tokio::spawn(async move {
+                let params = PaymentParams {
+                    actor: from,
+                    to,
+                    from: Some(from),
+                    amount: 1,
+                    memo: None,
+                    category: None,
+                    admin_override: None,
+                    idempotency_key: None,
+                };
+
+                payment(&params, None, &db).await
+            })
+        });
+
+        let mut succeeded = 0;
+        for payment in payments.collect::<Vec<_>>() {
+            match payment.await.unwrap() {
+                Ok(_) => succeeded += 1,
+                Err(Error::HttpError(ErrorCode::InsufficientFunds, _)) => {}
+                Err(err) => panic!("Unexpected error: {err}"),
+            }
+        }
+
+        let balances = ledger::lock_balances(&[from, to], &mut db.begin().await.unwrap()).await.unwrap();
+
+        assert_eq!(succeeded, AFFORDABLE_PAYMENTS);
+        assert_eq!(balances[&from], 0);
+        assert_eq!(balances[&to], AFFORDABLE_PAYMENTS);
+    }
+}
@@ -0,0 +1,93 @@
+use shared::types::{BalanceMismatch, ReconcileReport};
+use sqlx::PgPool;
+use std::time::Duration;
+use tracing::{event, Level};
+
+use crate::error::Result;
+
+/// Replays the ledger for every account and compares it to the stored balances,
+/// optionally overwriting drifted balances with what the ledger implies
+pub async fn reconcile(repair: bool, db: &PgPool) -> Result<ReconcileReport> {
+    let mut tx = db.begin().await?;
+
+    if repair {
+        // Stops payments from changing balances while they get compared
+        sqlx::query!("LOCK TABLE account IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut tx)
+            .await?;
+    }
+
+    let accounts_checked = sqlx::query!(r#"SELECT COUNT(*) as "count!" FROM account"#)
+        .fetch_one(&mut tx)
+        .await?
+        .count;
+
+    let mismatches: Vec<BalanceMismatch> = sqlx::query!(
+        r###"SELECT id, balance, expected as "expected!" FROM (
+            SELECT account.id, account.balance, COALESCE(postings.amount, 0)::BIGINT as expected
+            FROM account
+            LEFT JOIN (SELECT account_id, SUM(amount) as amount FROM posting GROUP BY account_id) postings ON postings.account_id = account.id
+        ) replayed
+        WHERE balance != expected
+        ORDER BY id"###
+    )
+    .fetch_all(&mut tx)
+    .await?
+    .into_iter()
+    .map(|row| BalanceMismatch {
+        account: row.id,
+        stored: row.balance,
+        expected: row.expected,
+    })
+    .collect();
+
+    for mismatch in &mismatches {
+        event!(
+            Level::WARN,
+            account = %mismatch.account,
+            stored = mismatch.stored,
+            expected = mismatch.expected,
+            repair,
+            "Balance does not match the ledger"
+        );
+    }
+
+    if repair {
+        for mismatch in &mismatches {
+            sqlx::query!(
+                "UPDATE account SET balance = $2 WHERE id = $1",
+                mismatch.account,
+                mismatch.expected
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(ReconcileReport {
+        accounts_checked,
+        repaired: repair && !mismatches.is_empty(),
+        mismatches,
+    })
+}
+
+/// Checks the ledger every `interval` without repairing, mismatches get logged
+pub async fn run_scheduled(interval: Duration, db: PgPool) {
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        interval.tick().await;
+
+        match reconcile(false, &db).await {
+            Ok(report) if report.mismatches.is_empty() => {
+                event!(Level::DEBUG, accounts = report.accounts_checked, "Ledger reconciled");
+            }
+            Ok(report) => {
+                event!(Level::ERROR, mismatches = report.mismatches.len(), "Ledger does not match balances");
+            }
+            Err(err) => event!(Level::ERROR, %err, "Failed to reconcile the ledger"),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use axum::{
+    Router,
+    routing::get,
+    extract::{Path, State, Query},
+    Json
+};
+use shared::error::ErrorCode;
+use shared::types::{AccountDetailsParams, AccountInfo};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::auth::{ApiKey, Scope};
+use crate::error::Error;
+use crate::error::Result;
+use crate::error::UniqueValueError;
+use crate::validate::validate_name;
+
+const MAX_DESCRIPTION_LENGTH: usize = 1024;
+
+pub fn routes() -> Router<PgPool> {
+    Router::new()
+        .route("/by_name/:name", get(get_by_name))
+        .route("/:id", get(get_account).put(set_details))
+}
+
+/// Checks that `actor` is a person allowed to change the details of `account`, either by owning it
+/// or by administrating the organisation behind it
+async fn authorize_manage(actor: Uuid, account: Uuid, db: impl PgExecutor<'_>) -> Result<()> {
+    if crate::payment::is_system_account(account) {
+        return Err(Error::HttpError(ErrorCode::Forbidden, "The mint and burn accounts cannot be changed"));
+    }
+
+    let authorization = sqlx::query!(
+        r###"SELECT EXISTS(SELECT 1 FROM person WHERE id = $2) as "person!", $1 = $2 OR EXISTS(
+            SELECT 1 FROM organisation WHERE id = $1 AND owner = $2
+            UNION ALL
+            SELECT 1 FROM organisation_member WHERE organisation_id = $1 AND member_id = $2 AND administrator
+        ) as "allowed!""###,
+        account,
+        actor
+    )
+    .fetch_one(db)
+    .await?;
+
+    if !authorization.person {
+        return Err(Error::NotFound("person"));
+    }
+
+    if !authorization.allowed {
+        return Err(Error::HttpError(ErrorCode::Forbidden, "You are not allowed to change this account"));
+    }
+
+    Ok(())
+}
+
+pub async fn get_account(key: ApiKey, Path(id): Path<Uuid>, State(db): State<PgPool>) -> Result<Json<AccountInfo>> {
+    key.require(Scope::Read)?;
+
+    let account = sqlx::query_as!(
+        AccountInfo,
+        "SELECT id, name, description, balance FROM account WHERE id = $1",
+        id
+    )
+    .fetch_optional(&db)
+    .await?
+    .ok_or(Error::NotFound("account"))?;
+
+    Ok(Json(account))
+}
+
+pub async fn get_by_name(key: ApiKey, Path(name): Path<String>, State(db): State<PgPool>) -> Result<Json<AccountInfo>> {
+    key.require(Scope::Read)?;
+
+    let account = sqlx::query_as!(
+        AccountInfo,
+        "SELECT id, name, description, balance FROM account WHERE LOWER(name) = LOWER($1)",
+        name.trim()
+    )
+    .fetch_optional(&db)
+    .await?
+    .ok_or(Error::NotFound("account"))?;
+
+    Ok(Json(account))
+}
+
+pub async fn set_details(
+    key: ApiKey,
+    Path(id): Path<Uuid>,
+    Query(params): Query<AccountDetailsParams>,
+    State(db): State<PgPool>,
+) -> Result<&'static str> {
+    key.require(Scope::Pay)?;
+
+    let name = params.name.as_deref().map(validate_name).transpose()?;
+
+    if matches!(&params.description, Some(description) if description.chars().count() > MAX_DESCRIPTION_LENGTH) {
+        return Err(Error::HttpError(ErrorCode::InvalidInput, "Descriptions can be at most 1024 characters"));
+    }
+
+    authorize_manage(params.actor, id, &db).await?;
+
+    let result = sqlx::query!(
+        "UPDATE account SET name = COALESCE($2, name), description = COALESCE($3, description) WHERE id = $1",
+        id,
+        name,
+        params.description
+    )
+    .execute(&db)
+    .await
+    .or_already_exists(ErrorCode::NameTaken, "This name is already taken!")?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("account"));
+    }
+
+    Ok("Updated account!")
+}
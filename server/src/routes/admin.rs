@@ -0,0 +1,141 @@
+use axum::{
+    Router,
+    routing::{delete, get, post},
+    extract::{Path, State, Query},
+    http::StatusCode,
+    Json
+};
+use shared::types::{ApiKeyInfo, ApiKeyParams, AuditEntry, AuditPage, AuditParams, CreatedApiKey, ReconcileParams, ReconcileReport};
+use sqlx::{types::Json as Jsonb, PgPool};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::auth::{self, ApiKey, Scope};
+use crate::error::Error;
+use crate::error::Result;
+use crate::timestamp::{self, UUIDTime};
+
+const DEFAULT_AUDIT_LIMIT: i64 = 50;
+const MAX_AUDIT_LIMIT: i64 = 200;
+
+pub fn routes() -> Router<PgPool> {
+    Router::new()
+        .route("/keys", get(list_keys).post(create_key))
+        .route("/keys/:id", delete(revoke_key))
+        .route("/reconcile", post(reconcile))
+        .route("/audit", get(get_audit))
+}
+
+pub async fn create_key(
+    key: ApiKey,
+    Query(params): Query<ApiKeyParams>,
+    State(db): State<PgPool>,
+) -> Result<(StatusCode, Json<CreatedApiKey>)> {
+    key.require(Scope::Admin)?;
+
+    let id = Uuid::now_v7();
+    let new_key = auth::generate_key();
+
+    sqlx::query!(
+        "INSERT INTO api_key (id, hash, name, read, pay, print, admin) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        id,
+        auth::hash_key(&new_key),
+        params.name,
+        params.read,
+        params.pay,
+        params.print,
+        params.admin
+    )
+    .execute(&db)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(CreatedApiKey { id, key: new_key })))
+}
+
+pub async fn list_keys(key: ApiKey, State(db): State<PgPool>) -> Result<Json<Vec<ApiKeyInfo>>> {
+    key.require(Scope::Admin)?;
+
+    let keys = sqlx::query_as!(
+        ApiKeyInfo,
+        "SELECT id, name, read, pay, print, admin, revoked FROM api_key ORDER BY id"
+    )
+    .fetch_all(&db)
+    .await?;
+
+    Ok(Json(keys))
+}
+
+pub async fn revoke_key(key: ApiKey, Path(id): Path<Uuid>, State(db): State<PgPool>) -> Result<&'static str> {
+    key.require(Scope::Admin)?;
+
+    let result = sqlx::query!("UPDATE api_key SET revoked = TRUE WHERE id = $1", id)
+        .execute(&db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("api key"));
+    }
+
+    Ok("Revoked key!")
+}
+
+/// Compares stored balances to the ledger, repairing them if asked to
+pub async fn reconcile(
+    key: ApiKey,
+    Query(params): Query<ReconcileParams>,
+    State(db): State<PgPool>,
+) -> Result<Json<ReconcileReport>> {
+    key.require(Scope::Admin)?;
+
+    let report = crate::reconcile::reconcile(params.repair.unwrap_or(false), &db).await?;
+
+    Ok(Json(report))
+}
+
+pub async fn get_audit(
+    key: ApiKey,
+    Query(params): Query<AuditParams>,
+    State(db): State<PgPool>,
+) -> Result<Json<AuditPage>> {
+    key.require(Scope::Admin)?;
+
+    let limit = params.limit.unwrap_or(DEFAULT_AUDIT_LIMIT).clamp(1, MAX_AUDIT_LIMIT);
+
+    let (lower, upper) = timestamp::page_bounds(params.since, params.until, params.cursor)?;
+
+    let mut entries: Vec<AuditEntry> = sqlx::query!(
+        r###"SELECT id, api_key_id, action, actor, target, parameters as "parameters: Jsonb<BTreeMap<String, String>>", status
+        FROM audit_log
+        WHERE id >= $1 AND id < $2
+            AND ($3::UUID IS NULL OR api_key_id = $3)
+            AND ($4::UUID IS NULL OR actor = $4)
+            AND ($5::UUID IS NULL OR target = $5)
+            AND ($6::TEXT IS NULL OR action = $6)
+        ORDER BY id DESC LIMIT $7"###,
+        lower,
+        upper,
+        params.api_key,
+        params.actor,
+        params.target,
+        params.action,
+        limit + 1
+    )
+    .fetch_all(&db)
+    .await?
+    .into_iter()
+    .map(|row| AuditEntry {
+        id: row.id,
+        api_key: row.api_key_id,
+        action: row.action,
+        actor: row.actor,
+        target: row.target,
+        parameters: row.parameters.0,
+        status: row.status,
+        time: UUIDTime::from(row.id).to_unix_sec(),
+    })
+    .collect();
+
+    let next_cursor = timestamp::next_cursor(&mut entries, limit, |entry| entry.id);
+
+    Ok(Json(AuditPage { entries, next_cursor }))
+}
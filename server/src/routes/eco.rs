@@ -1,52 +1,424 @@
 use axum::{
     Router, 
     routing::{get, post}, 
-    extract::{Path, State, Query}
+    extract::{Path, State, Query},
+    Json
 };
-use shared::types::PaymentParams;
+use shared::error::ErrorCode;
+use shared::types::{BurnParams, EconomyStats, HistoryPage, HistoryParams, Leaderboard, LeaderboardEntry, LeaderboardParams, PaymentParams, Posting, PrintParams, Receipt, ReverseParams, TransactionEntry, BURN_ACCOUNT, MINT_ACCOUNT};
 use sqlx::PgPool;
+use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+use crate::auth::{ApiKey, Scope};
+use crate::discord_id::DiscordId;
+use crate::error::Error;
 use crate::error::Result;
+use crate::ledger;
+use crate::payment::Override;
+use crate::timestamp::{self, UUIDTime};
+use crate::validate::validate_reason;
 
+const DEFAULT_HISTORY_LIMIT: i64 = 25;
+const MAX_HISTORY_LIMIT: i64 = 100;
+const DEFAULT_LEADERBOARD_LIMIT: i64 = 10;
+const MAX_LEADERBOARD_LIMIT: i64 = 100;
+const DAY_SECS: u64 = 24 * 60 * 60;
+const WEEK_SECS: u64 = 7 * DAY_SECS;
+
+// Router is already must_use, which newer clippy flags as doubled up
+#[allow(clippy::double_must_use)]
 #[must_use]
 pub fn routes() -> Router<PgPool> {
     Router::new()
         .route("/payment", post(payment))
         .route("/print/:id/:amount", post(print_money))
+        .route("/burn/:id/:amount", post(burn_money))
         .route("/balance/:id", get(get_balance))
+        .route("/history/:id", get(get_history))
+        .route("/leaderboard", get(get_leaderboard))
+        .route("/stats", get(get_stats))
+        .route("/transaction/:id/reverse", post(reverse))
 }
 
 pub async fn payment(
+    key: ApiKey,
     queries: Query<PaymentParams>,
     State(db): State<PgPool>,
-) -> Result<&'static str> {
-    crate::payment::payment(queries.to, queries.from, queries.amount, queries.force, &db).await?;
+) -> Result<Json<Receipt>> {
+    key.require(Scope::Pay)?;
+
+    let admin_override = admin_override(&key, queries.admin_override.as_deref())?;
+    let receipt = crate::payment::payment(&queries, admin_override.as_ref(), &db).await?;
 
-    Ok("Success")
+    Ok(Json(receipt))
 }
 
 pub async fn print_money(
+    key: ApiKey,
     Path((id, amount)): Path<(Uuid, i64)>,
+    Query(params): Query<PrintParams>,
     State(db): State<PgPool>,
-) -> Result<&'static str> {
+) -> Result<Json<Receipt>> {
+    key.require(Scope::Print)?;
 
-    sqlx::query!(
-        "UPDATE account SET balance = balance + $1 WHERE id = $2",
-        amount,
-        id
-    )
-    .execute(&db)
-    .await?;
+    if amount <= 0 {
+        return Err(Error::HttpError(ErrorCode::InvalidAmount, "You can only print a positive amount of money"));
+    }
+
+    if crate::payment::is_system_account(id) {
+        return Err(Error::HttpError(ErrorCode::Forbidden, "Money cannot be printed into the mint or burn account"));
+    }
+
+    let reason = validate_reason(&params.reason)?;
+
+    let mut tx = db.begin().await?;
+
+    sqlx::query!("SELECT id FROM account WHERE id = $1", id)
+        .fetch_optional(&mut tx)
+        .await?
+        .ok_or(Error::NotFound("account"))?;
+
+    let receipt = crate::payment::transfer(MINT_ACCOUNT, id, amount, Some(reason), None, None, &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(receipt))
+}
+
+pub async fn burn_money(
+    key: ApiKey,
+    Path((id, amount)): Path<(Uuid, i64)>,
+    Query(params): Query<BurnParams>,
+    State(db): State<PgPool>,
+) -> Result<Json<Receipt>> {
+    key.require(Scope::Print)?;
+
+    if amount <= 0 {
+        return Err(Error::HttpError(ErrorCode::InvalidAmount, "You can only burn a positive amount of money"));
+    }
+
+    if crate::payment::is_system_account(id) {
+        return Err(Error::HttpError(ErrorCode::Forbidden, "Money cannot be burned from the mint or burn account"));
+    }
+
+    let reason = validate_reason(&params.reason)?;
+
+    let mut tx = db.begin().await?;
+
+    let balance = *ledger::lock_balances(&[id, BURN_ACCOUNT], &mut tx)
+        .await?
+        .get(&id)
+        .ok_or(Error::NotFound("account"))?;
+
+    if balance < amount {
+        return Err(Error::HttpError(ErrorCode::InsufficientFunds, "The account lacks the funds to burn this much"));
+    }
 
-    Ok("Printed money!")
+    let receipt = crate::payment::transfer(id, BURN_ACCOUNT, amount, Some(reason), None, None, &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(receipt))
+}
+
+pub async fn reverse(
+    key: ApiKey,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ReverseParams>,
+    State(db): State<PgPool>,
+) -> Result<Json<Receipt>> {
+    key.require(Scope::Pay)?;
+
+    let admin_override = admin_override(&key, params.admin_override.as_deref())?;
+    let reason = params.reason.as_deref().map(validate_reason).transpose()?;
+
+    let receipt = crate::payment::reverse(id, &params, reason, admin_override.as_ref(), &db).await?;
+
+    Ok(Json(receipt))
+}
+
+/// Only admin keys may override and they have to say why
+fn admin_override<'a>(key: &ApiKey, reason: Option<&'a str>) -> Result<Option<Override<'a>>> {
+    let Some(reason) = reason else {
+        return Ok(None);
+    };
+
+    key.require(Scope::Admin)?;
+
+    Ok(Some(Override {
+        api_key: key.id,
+        reason: validate_reason(reason)?,
+    }))
 }
 
-pub async fn get_balance(Path(id): Path<Uuid>, State(db): State<PgPool>) -> Result<String> {
+pub async fn get_balance(key: ApiKey, Path(id): Path<Uuid>, State(db): State<PgPool>) -> Result<String> {
+    key.require(Scope::Read)?;
+
     let balance = sqlx::query!("SELECT balance FROM account WHERE id = $1", id)
-        .fetch_one(&db)
+        .fetch_optional(&db)
         .await?
+        .ok_or(Error::NotFound("account"))?
         .balance;
 
     Ok(balance.to_string())
 }
+
+pub async fn get_history(
+    key: ApiKey,
+    Path(id): Path<Uuid>,
+    Query(params): Query<HistoryParams>,
+    State(db): State<PgPool>,
+) -> Result<Json<HistoryPage>> {
+    key.require(Scope::Read)?;
+
+    sqlx::query!("SELECT id FROM account WHERE id = $1", id)
+        .fetch_optional(&db)
+        .await?
+        .ok_or(Error::NotFound("account"))?;
+
+    let limit = params.limit.unwrap_or(DEFAULT_HISTORY_LIMIT).clamp(1, MAX_HISTORY_LIMIT);
+
+    let (lower, upper) = timestamp::page_bounds(params.since, params.until, params.cursor)?;
+
+    let rows = sqlx::query!(
+        r###"SELECT posting.transaction_id, posting.account_id, posting.amount, ledger_transaction.memo, ledger_transaction.category,
+            ledger_transaction.reverses,
+            ARRAY(SELECT id FROM ledger_transaction reversal WHERE reversal.reverses = posting.transaction_id ORDER BY id) as "reversed_by!"
+        FROM posting
+        INNER JOIN ledger_transaction ON ledger_transaction.id = posting.transaction_id
+        WHERE posting.transaction_id IN (
+            SELECT DISTINCT transaction_id FROM posting
+            INNER JOIN ledger_transaction ON ledger_transaction.id = posting.transaction_id
+            WHERE account_id = $1 AND transaction_id >= $2 AND transaction_id < $3 AND ($5::TEXT IS NULL OR category = $5)
+            ORDER BY transaction_id DESC LIMIT $4
+        )
+        ORDER BY posting.transaction_id DESC, posting.line"###,
+        id,
+        lower,
+        upper,
+        limit + 1,
+        params.category.as_deref().map(|category| category.trim().to_lowercase())
+    )
+    .fetch_all(&db)
+    .await?;
+
+    let mut entries: Vec<TransactionEntry> = Vec::new();
+
+    for row in rows {
+        let posting = Posting { account: row.account_id, amount: row.amount };
+
+        match entries.last_mut() {
+            Some(entry) if entry.id == row.transaction_id => entry.postings.push(posting),
+            _ => entries.push(TransactionEntry {
+                id: row.transaction_id,
+                postings: vec![posting],
+                memo: row.memo,
+                category: row.category,
+                reverses: row.reverses,
+                reversed_by: row.reversed_by,
+                time: UUIDTime::from(row.transaction_id).to_unix_sec(),
+            }),
+        }
+    }
+
+    let next_cursor = timestamp::next_cursor(&mut entries, limit, |entry| entry.id);
+
+    Ok(Json(HistoryPage { entries, next_cursor }))
+}
+
+/// Ranks the personal accounts of people by balance
+pub async fn get_leaderboard(
+    key: ApiKey,
+    Query(params): Query<LeaderboardParams>,
+    State(db): State<PgPool>,
+) -> Result<Json<Leaderboard>> {
+    key.require(Scope::Read)?;
+
+    let limit = params.limit.unwrap_or(DEFAULT_LEADERBOARD_LIMIT).clamp(1, MAX_LEADERBOARD_LIMIT);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let entries = sqlx::query!(
+        r###"SELECT RANK() OVER (ORDER BY balance DESC) as "rank!", person.id, discord_id as "discord_id: DiscordId", balance
+        FROM person INNER JOIN account ON account.id = person.id
+        ORDER BY balance DESC, person.id LIMIT $1 OFFSET $2"###,
+        limit,
+        offset
+    )
+    .fetch_all(&db)
+    .await?
+    .into_iter()
+    .map(|row| LeaderboardEntry {
+        rank: row.rank,
+        id: row.id,
+        discord_id: row.discord_id.into(),
+        balance: row.balance,
+    })
+    .collect();
+
+    let person = match params.person {
+        Some(person) => {
+            let row = sqlx::query!(
+                r###"SELECT rank as "rank!", id as "id!", discord_id as "discord_id!: DiscordId", balance as "balance!" FROM (
+                    SELECT RANK() OVER (ORDER BY balance DESC) as rank, person.id, discord_id, balance
+                    FROM person INNER JOIN account ON account.id = person.id
+                ) ranked
+                WHERE id = $1"###,
+                person
+            )
+            .fetch_optional(&db)
+            .await?
+            .ok_or(Error::NotFound("person"))?;
+
+            Some(LeaderboardEntry {
+                rank: row.rank,
+                id: row.id,
+                discord_id: row.discord_id.into(),
+                balance: row.balance,
+            })
+        }
+        None => None,
+    };
+
+    Ok(Json(Leaderboard { entries, person }))
+}
+
+pub async fn get_stats(key: ApiKey, State(db): State<PgPool>) -> Result<Json<EconomyStats>> {
+    key.require(Scope::Read)?;
+
+    // Everything about the distribution of balances is worked out by the database,
+    // so the accounts never have to be loaded
+    let distribution = sqlx::query!(
+        r###"SELECT
+            COUNT(*) as "accounts!",
+            COALESCE(SUM(balance), 0)::BIGINT as "money_supply!",
+            percentile_cont(0.5) WITHIN GROUP (ORDER BY balance) as median,
+            COALESCE(SUM((2 * rank - count - 1) * balance::FLOAT8), 0) as "weighted!",
+            COALESCE((SELECT -balance FROM account WHERE id = $1), 0) as "minted!",
+            COALESCE((SELECT balance FROM account WHERE id = $2), 0) as "burned!"
+        FROM (
+            SELECT balance, ROW_NUMBER() OVER (ORDER BY balance) as rank, COUNT(*) OVER () as count
+            FROM account WHERE id != $1 AND id != $2
+        ) AS balances"###,
+        MINT_ACCOUNT,
+        BURN_ACCOUNT
+    )
+    .fetch_one(&db)
+    .await?;
+
+    let persons = sqlx::query!(r#"SELECT COUNT(*) as "count!" FROM person"#)
+        .fetch_one(&db)
+        .await?
+        .count;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let day = UUIDTime::from_unix_sec(now.saturating_sub(DAY_SECS))?.first_uuid();
+    let week = UUIDTime::from_unix_sec(now.saturating_sub(WEEK_SECS))?.first_uuid();
+
+    let activity = sqlx::query!(
+        r###"SELECT
+            COUNT(DISTINCT transaction_id) FILTER (WHERE transaction_id >= $1) as "transactions_day!",
+            COUNT(DISTINCT transaction_id) as "transactions_week!",
+            COALESCE(SUM(amount) FILTER (WHERE transaction_id >= $1), 0)::BIGINT as "volume_day!",
+            COALESCE(SUM(amount), 0)::BIGINT as "volume_week!"
+        FROM posting WHERE transaction_id >= $2 AND amount > 0"###,
+        day,
+        week
+    )
+    .fetch_one(&db)
+    .await?;
+
+    Ok(Json(EconomyStats {
+        money_supply: distribution.money_supply,
+        accounts: distribution.accounts,
+        persons,
+        transactions_day: activity.transactions_day,
+        transactions_week: activity.transactions_week,
+        volume_day: activity.volume_day,
+        volume_week: activity.volume_week,
+        median_balance: median(distribution.median),
+        gini: gini(distribution.accounts, distribution.money_supply, distribution.weighted),
+        minted: distribution.minted,
+        burned: distribution.burned,
+    }))
+}
+
+/// Rounds the median the database interpolated, which is between the middle two balances for an even count
+#[allow(clippy::cast_possible_truncation)]
+fn median(percentile: Option<f64>) -> i64 {
+    percentile.map_or(0, |median| median.round() as i64)
+}
+
+/// Gini coefficient from the number of accounts, their total balance and the sum of each balance
+/// weighted by `2 * rank - accounts - 1` in ascending order. Scaled by n / (n - 1)
+/// so it reaches 1 when one account has everything.
+#[allow(clippy::cast_precision_loss)]
+fn gini(accounts: i64, total: i64, weighted: f64) -> f64 {
+    // Not meaningful when accounts are in debt overall or there is nobody to compare with
+    if total <= 0 || accounts < 2 {
+        return 0.0;
+    }
+
+    weighted / ((accounts - 1) as f64 * total as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What the stats query weighs balances by, for balances sorted in ascending order
+    #[allow(clippy::cast_precision_loss)]
+    fn weighted(sorted_balances: &[i64]) -> f64 {
+        let count = sorted_balances.len() as f64;
+
+        (1_u32..)
+            .zip(sorted_balances)
+            .map(|(rank, &balance)| (2.0 * f64::from(rank) - count - 1.0) * balance as f64)
+            .sum()
+    }
+
+    #[test]
+    fn median_odd_count() {
+        // percentile_cont of 1, 4 and 9
+        assert_eq!(median(Some(4.0)), 4);
+    }
+
+    #[test]
+    fn median_even_count() {
+        // percentile_cont of 1, 4, 9 and 12 averages the middle two
+        assert_eq!(median(Some(6.5)), 7);
+        // and of 10 and 20
+        assert_eq!(median(Some(15.0)), 15);
+    }
+
+    #[test]
+    fn median_no_accounts() {
+        assert_eq!(median(None), 0);
+    }
+
+    #[test]
+    fn gini_equal_balances() {
+        let balances = [25, 25, 25, 25];
+
+        assert!(gini(4, 100, weighted(&balances)).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn gini_one_account_has_everything() {
+        let balances = [0, 0, 0, 100];
+
+        assert!((gini(4, 100, weighted(&balances)) - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn gini_fewer_than_two_accounts() {
+        assert!(gini(1, 100, weighted(&[100])).abs() < f64::EPSILON);
+        assert!(gini(0, 0, 0.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn gini_no_money() {
+        assert!(gini(2, 0, weighted(&[0, 0])).abs() < f64::EPSILON);
+        assert!(gini(2, -10, weighted(&[-20, 10])).abs() < f64::EPSILON);
+    }
+}
@@ -1,2 +1,5 @@
+pub mod account;
+pub mod admin;
 pub mod eco;
+pub mod org;
 pub mod person;
@@ -0,0 +1,245 @@
+use axum::{
+    Router,
+    routing::{get, post, put},
+    extract::{Path, State, Query},
+    http::StatusCode,
+    Json
+};
+use shared::error::ErrorCode;
+use shared::types::{ActorParams, CreateOrganisationParams, MemberFlagsParams, OrganisationInfo, OrganisationMember};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::auth::{ApiKey, Scope};
+use crate::error::Error;
+use crate::error::Result;
+use crate::error::UniqueValueError;
+use crate::routes::person::{reset_account_in_use, verify_person};
+use crate::validate::validate_name;
+
+pub fn routes() -> Router<PgPool> {
+    Router::new()
+        .route("/create", post(create))
+        .route("/:id", get(get_organisation))
+        .route("/:id/invite/:member", post(invite))
+        .route("/:id/members/:member", put(set_flags).delete(remove_member))
+}
+
+/// What a person is allowed to do within an organisation, the owner is allowed everything
+#[allow(clippy::struct_excessive_bools)]
+pub struct Permissions {
+    pub owner: bool,
+    pub inviter: bool,
+    pub accountant: bool,
+    pub administrator: bool,
+}
+
+pub async fn permissions(organisation: Uuid, actor: Uuid, db: impl PgExecutor<'_>) -> Result<Permissions> {
+    let result = sqlx::query!(
+        r###"SELECT organisation.owner = $2 as "owner!", COALESCE(member.inviter, FALSE) as "inviter!", COALESCE(member.accountant, FALSE) as "accountant!", COALESCE(member.administrator, FALSE) as "administrator!"
+        FROM organisation LEFT JOIN organisation_member member ON member.organisation_id = organisation.id AND member.member_id = $2
+        WHERE organisation.id = $1"###,
+        organisation,
+        actor
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or(Error::NotFound("organisation"))?;
+
+    Ok(Permissions {
+        owner: result.owner,
+        inviter: result.owner || result.inviter,
+        accountant: result.owner || result.accountant,
+        administrator: result.owner || result.administrator,
+    })
+}
+
+pub async fn create(
+    key: ApiKey,
+    Query(params): Query<CreateOrganisationParams>,
+    State(db): State<PgPool>,
+) -> Result<(StatusCode, String)> {
+    key.require(Scope::Pay)?;
+
+    let mut tx = db.begin().await?;
+
+    verify_person(params.owner, &mut tx).await?;
+
+    let uuid = Uuid::now_v7();
+    let name = match &params.name {
+        Some(name) => validate_name(name)?.to_string(),
+        None => uuid.as_simple().to_string(),
+    };
+
+    sqlx::query!("INSERT INTO account (id, name) VALUES ($1, $2)", &uuid, name)
+        .execute(&mut tx)
+        .await
+        .or_already_exists(ErrorCode::NameTaken, "This name is already taken!")?;
+
+    sqlx::query!(
+        "INSERT INTO organisation (id, owner, public) VALUES ($1, $2, $3)",
+        &uuid,
+        params.owner,
+        params.public.unwrap_or(false)
+    )
+    .execute(&mut tx)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO organisation_member (member_id, organisation_id, inviter, accountant, administrator) VALUES ($1, $2, TRUE, TRUE, TRUE)",
+        params.owner,
+        &uuid
+    )
+    .execute(&mut tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((StatusCode::CREATED, uuid.as_simple().to_string()))
+}
+
+pub async fn get_organisation(
+    key: ApiKey,
+    Path(id): Path<Uuid>,
+    State(db): State<PgPool>,
+) -> Result<Json<OrganisationInfo>> {
+    key.require(Scope::Read)?;
+
+    let organisation = sqlx::query!(
+        "SELECT owner, public, balance FROM organisation INNER JOIN account ON account.id=organisation.id WHERE organisation.id = $1",
+        id
+    )
+    .fetch_optional(&db)
+    .await?
+    .ok_or(Error::NotFound("organisation"))?;
+
+    let members = sqlx::query_as!(
+        OrganisationMember,
+        "SELECT member_id as id, inviter, accountant, administrator FROM organisation_member WHERE organisation_id = $1 ORDER BY creation_time",
+        id
+    )
+    .fetch_all(&db)
+    .await?;
+
+    Ok(Json(OrganisationInfo {
+        id,
+        owner: organisation.owner,
+        public: organisation.public,
+        balance: organisation.balance,
+        members,
+    }))
+}
+
+pub async fn invite(
+    key: ApiKey,
+    Path((id, member)): Path<(Uuid, Uuid)>,
+    Query(params): Query<ActorParams>,
+    State(db): State<PgPool>,
+) -> Result<(StatusCode, &'static str)> {
+    key.require(Scope::Pay)?;
+
+    if !permissions(id, params.actor, &db).await?.inviter {
+        return Err(Error::HttpError(ErrorCode::Forbidden, "You are not allowed to invite members"));
+    }
+
+    verify_person(member, &db).await?;
+
+    sqlx::query!(
+        "INSERT INTO organisation_member (member_id, organisation_id) VALUES ($1, $2)",
+        member,
+        id
+    )
+    .execute(&db)
+    .await
+    .or_already_exists(ErrorCode::AlreadyExists, "This person is already a member!")?;
+
+    Ok((StatusCode::CREATED, "Added member!"))
+}
+
+pub async fn remove_member(
+    key: ApiKey,
+    Path((id, member)): Path<(Uuid, Uuid)>,
+    Query(params): Query<ActorParams>,
+    State(db): State<PgPool>,
+) -> Result<&'static str> {
+    key.require(Scope::Pay)?;
+
+    let permissions = permissions(id, params.actor, &db).await?;
+
+    if !permissions.administrator && params.actor != member {
+        return Err(Error::HttpError(ErrorCode::Forbidden, "You are not allowed to remove members"));
+    }
+
+    let owner = sqlx::query!("SELECT owner FROM organisation WHERE id = $1", id)
+        .fetch_one(&db)
+        .await?
+        .owner;
+
+    if owner == member {
+        return Err(Error::HttpError(ErrorCode::InvalidOperation, "The owner cannot be removed from their organisation"));
+    }
+
+    let mut tx = db.begin().await?;
+
+    let result = sqlx::query!(
+        "DELETE FROM organisation_member WHERE organisation_id = $1 AND member_id = $2",
+        id,
+        member
+    )
+    .execute(&mut tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("member"));
+    }
+
+    reset_account_in_use(member, id, &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok("Removed member!")
+}
+
+pub async fn set_flags(
+    key: ApiKey,
+    Path((id, member)): Path<(Uuid, Uuid)>,
+    Query(params): Query<MemberFlagsParams>,
+    State(db): State<PgPool>,
+) -> Result<&'static str> {
+    key.require(Scope::Pay)?;
+
+    let permissions = permissions(id, params.actor, &db).await?;
+
+    if !permissions.administrator {
+        return Err(Error::HttpError(ErrorCode::Forbidden, "You are not allowed to change member permissions"));
+    }
+
+    if params.administrator.is_some() && !permissions.owner {
+        return Err(Error::HttpError(ErrorCode::Forbidden, "Only the owner can change who is an administrator"));
+    }
+
+    let mut tx = db.begin().await?;
+
+    let result = sqlx::query!(
+        "UPDATE organisation_member SET inviter = COALESCE($3, inviter), accountant = COALESCE($4, accountant), administrator = COALESCE($5, administrator) WHERE organisation_id = $1 AND member_id = $2",
+        id,
+        member,
+        params.inviter,
+        params.accountant,
+        params.administrator
+    )
+    .execute(&mut tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("member"));
+    }
+
+    if params.accountant == Some(false) {
+        reset_account_in_use(member, id, &mut tx).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok("Updated member!")
+}
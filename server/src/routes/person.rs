@@ -1,51 +1,90 @@
 use axum::{
     Router, 
-    routing::{get, post},
-    extract::{Path, State},
+    routing::{get, post, put},
+    extract::{Path, State, Query},
     http::StatusCode,
     Json
 };
-use shared::types::PersonInfo;
-use sqlx::PgPool;
+use shared::error::ErrorCode;
+use shared::types::{ActiveBalance, FriendAccountantParams, FriendInfo, FriendRequest, FriendRequests, PersonInfo};
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 
+use crate::auth::{ApiKey, Scope};
 use crate::discord_id::DiscordId;
 use crate::error::Error;
 use crate::error::Result;
 use crate::error::UniqueValueError;
+use crate::payment::{account_in_use, authorize_spend};
 
+// Router is already must_use, which newer clippy flags as doubled up
+#[allow(clippy::double_must_use)]
 #[must_use]
 pub fn routes() -> Router<PgPool> {
     Router::new()
         .route("/register/:discord_id", post(register))
         .route("/from_discord/:discord_id", get(get_from_discord))
         .route("/:id", get(get_from_uuid))
+        .route("/:id/balance", get(get_active_balance))
+        .route("/:id/account_in_use/:account", put(set_account_in_use))
+        .route("/:id/friends", get(get_friends))
+        .route("/:id/friends/requests", get(get_friend_requests))
+        .route("/:id/friends/:friend", put(set_friend_accountant).delete(remove_friend))
+        .route("/:id/friends/:friend/request", post(send_friend_request))
+        .route("/:id/friends/:friend/accept", post(accept_friend_request))
+        .route("/:id/friends/:friend/decline", post(decline_friend_request))
 }
 
-pub async fn register(Path(discord_id): Path<u64>, State(db): State<PgPool>) -> Result<(StatusCode, String)> {
+pub async fn verify_person(id: Uuid, db: impl PgExecutor<'_>) -> Result<()> {
+    sqlx::query!("SELECT id FROM person WHERE id = $1", id)
+        .fetch_optional(db)
+        .await?
+        .ok_or(Error::NotFound("person"))?;
+
+    Ok(())
+}
+
+/// Switches a person back to their own account if they were using `account`
+pub async fn reset_account_in_use(person: Uuid, account: Uuid, db: impl PgExecutor<'_>) -> Result<()> {
+    sqlx::query!(
+        "UPDATE person SET account_in_use = id WHERE id = $1 AND account_in_use = $2",
+        person,
+        account
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn register(key: ApiKey, Path(discord_id): Path<u64>, State(db): State<PgPool>) -> Result<(StatusCode, String)> {
+    key.require(Scope::Pay)?;
+
     let mut tx = db.begin().await?;
 
     let uuid = Uuid::now_v7();
 
-    sqlx::query!("INSERT INTO account (id) VALUES ($1)", &uuid)
+    sqlx::query!("INSERT INTO account (id, name) VALUES ($1, $2)", &uuid, uuid.as_simple().to_string())
         .execute(&mut tx)
         .await?;
 
     sqlx::query!(
-        "INSERT INTO person (id, discord_id) VALUES ($1, $2)",
+        "INSERT INTO person (id, discord_id, account_in_use) VALUES ($1, $2, $1)",
         &uuid,
         &*DiscordId::from(discord_id)
     )
     .execute(&mut tx)
     .await
-    .or_already_exists("This person is already registered!")?;
+    .or_already_exists(ErrorCode::AlreadyRegistered, "This person is already registered!")?;
 
     tx.commit().await?;
 
     Ok((StatusCode::CREATED, uuid.as_simple().to_string()))
 }
 
-pub async fn get_from_discord(Path(discord_id): Path<u64>, State(db): State<PgPool>) -> Result<String> {
+pub async fn get_from_discord(key: ApiKey, Path(discord_id): Path<u64>, State(db): State<PgPool>) -> Result<String> {
+    key.require(Scope::Read)?;
+
     let result = sqlx::query!("SELECT id FROM person WHERE person.discord_id = $1", &discord_id.to_be_bytes())
                             .fetch_optional(&db)
                             .await?
@@ -54,16 +93,260 @@ pub async fn get_from_discord(Path(discord_id): Path<u64>, State(db): State<PgPo
     Ok(result.as_simple().to_string())
 }
 
-pub async fn get_from_uuid(Path(id): Path<Uuid>, State(db): State<PgPool>) -> Result<Json<PersonInfo>> {
-    let result = sqlx::query!(r###"SELECT discord_id as "discord_id: DiscordId" , balance FROM person INNER JOIN account ON account.id=person.id WHERE person.id = $1"###, id)
+pub async fn get_from_uuid(key: ApiKey, Path(id): Path<Uuid>, State(db): State<PgPool>) -> Result<Json<PersonInfo>> {
+    key.require(Scope::Read)?;
+
+    let result = sqlx::query!(r###"SELECT discord_id as "discord_id: DiscordId", name, description, balance, account_in_use FROM person INNER JOIN account ON account.id=person.id WHERE person.id = $1"###, id)
                             .fetch_optional(&db)
                             .await?
                             .ok_or(Error::NotFound("person"))?;
 
     let result = PersonInfo {
         discord_id: result.discord_id.into(),
+        name: result.name,
+        description: result.description,
         balance: result.balance,
+        account_in_use: result.account_in_use,
     };
 
     Ok(Json(result))
-}
\ No newline at end of file
+}
+
+pub async fn get_active_balance(key: ApiKey, Path(id): Path<Uuid>, State(db): State<PgPool>) -> Result<Json<ActiveBalance>> {
+    key.require(Scope::Read)?;
+
+    let result = sqlx::query!(
+        "SELECT account_in_use, balance FROM person INNER JOIN account ON account.id=person.account_in_use WHERE person.id = $1",
+        id
+    )
+    .fetch_optional(&db)
+    .await?
+    .ok_or(Error::NotFound("person"))?;
+
+    Ok(Json(ActiveBalance {
+        account: result.account_in_use,
+        balance: result.balance,
+    }))
+}
+
+/// Makes a person act with another account by default, they need to be allowed to spend from it
+pub async fn set_account_in_use(
+    key: ApiKey,
+    Path((id, account)): Path<(Uuid, Uuid)>,
+    State(db): State<PgPool>,
+) -> Result<&'static str> {
+    key.require(Scope::Pay)?;
+
+    let mut tx = db.begin().await?;
+
+    // Makes sure the person exists before checking their rights
+    account_in_use(id, &mut tx).await?;
+    authorize_spend(id, account, &mut tx).await?;
+
+    sqlx::query!("UPDATE person SET account_in_use = $2 WHERE id = $1", id, account)
+        .execute(&mut tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok("Switched account!")
+}
+
+pub async fn get_friends(key: ApiKey, Path(id): Path<Uuid>, State(db): State<PgPool>) -> Result<Json<Vec<FriendInfo>>> {
+    key.require(Scope::Read)?;
+
+    verify_person(id, &db).await?;
+
+    let friends = sqlx::query!(
+        r###"SELECT friend.friend_id, person.discord_id as "discord_id: DiscordId", friend.accountant FROM friend INNER JOIN person ON person.id=friend.friend_id WHERE friend.person_id = $1 ORDER BY friend.friend_id"###,
+        id
+    )
+    .fetch_all(&db)
+    .await?
+    .into_iter()
+    .map(|row| FriendInfo {
+        id: row.friend_id,
+        discord_id: row.discord_id.into(),
+        accountant: row.accountant,
+    })
+    .collect();
+
+    Ok(Json(friends))
+}
+
+pub async fn get_friend_requests(key: ApiKey, Path(id): Path<Uuid>, State(db): State<PgPool>) -> Result<Json<FriendRequests>> {
+    key.require(Scope::Read)?;
+
+    verify_person(id, &db).await?;
+
+    let incoming = sqlx::query!(
+        r###"SELECT sender_id, person.discord_id as "discord_id: DiscordId" FROM friend_request INNER JOIN person ON person.id=friend_request.sender_id WHERE receiver_id = $1 ORDER BY creation_time"###,
+        id
+    )
+    .fetch_all(&db)
+    .await?
+    .into_iter()
+    .map(|row| FriendRequest {
+        id: row.sender_id,
+        discord_id: row.discord_id.into(),
+    })
+    .collect();
+
+    let outgoing = sqlx::query!(
+        r###"SELECT receiver_id, person.discord_id as "discord_id: DiscordId" FROM friend_request INNER JOIN person ON person.id=friend_request.receiver_id WHERE sender_id = $1 ORDER BY creation_time"###,
+        id
+    )
+    .fetch_all(&db)
+    .await?
+    .into_iter()
+    .map(|row| FriendRequest {
+        id: row.receiver_id,
+        discord_id: row.discord_id.into(),
+    })
+    .collect();
+
+    Ok(Json(FriendRequests { incoming, outgoing }))
+}
+
+pub async fn send_friend_request(
+    key: ApiKey,
+    Path((id, friend)): Path<(Uuid, Uuid)>,
+    State(db): State<PgPool>,
+) -> Result<(StatusCode, &'static str)> {
+    key.require(Scope::Pay)?;
+
+    if id == friend {
+        return Err(Error::HttpError(ErrorCode::InvalidOperation, "You cannot befriend yourself"));
+    }
+
+    verify_person(id, &db).await?;
+    verify_person(friend, &db).await?;
+
+    let already_friends = sqlx::query!("SELECT friend_id FROM friend WHERE friend_id = $1 AND person_id = $2", friend, id)
+        .fetch_optional(&db)
+        .await?
+        .is_some();
+
+    if already_friends {
+        return Err(Error::AlreadyExists(ErrorCode::AlreadyExists, "You are already friends!"));
+    }
+
+    sqlx::query!("INSERT INTO friend_request (sender_id, receiver_id) VALUES ($1, $2)", id, friend)
+        .execute(&db)
+        .await
+        .or_already_exists(ErrorCode::AlreadyExists, "This friend request was already sent!")?;
+
+    Ok((StatusCode::CREATED, "Sent friend request!"))
+}
+
+pub async fn accept_friend_request(
+    key: ApiKey,
+    Path((id, friend)): Path<(Uuid, Uuid)>,
+    State(db): State<PgPool>,
+) -> Result<&'static str> {
+    key.require(Scope::Pay)?;
+
+    let mut tx = db.begin().await?;
+
+    let result = sqlx::query!("DELETE FROM friend_request WHERE sender_id = $1 AND receiver_id = $2", friend, id)
+        .execute(&mut tx)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("friend request"));
+    }
+
+    // A request the other way around is now pointless
+    sqlx::query!("DELETE FROM friend_request WHERE sender_id = $1 AND receiver_id = $2", id, friend)
+        .execute(&mut tx)
+        .await?;
+
+    sqlx::query!("INSERT INTO friend (friend_id, person_id) VALUES ($1, $2), ($2, $1)", friend, id)
+        .execute(&mut tx)
+        .await
+        .or_already_exists(ErrorCode::AlreadyExists, "You are already friends!")?;
+
+    tx.commit().await?;
+
+    Ok("Accepted friend request!")
+}
+
+pub async fn decline_friend_request(
+    key: ApiKey,
+    Path((id, friend)): Path<(Uuid, Uuid)>,
+    State(db): State<PgPool>,
+) -> Result<&'static str> {
+    key.require(Scope::Pay)?;
+
+    let result = sqlx::query!("DELETE FROM friend_request WHERE sender_id = $1 AND receiver_id = $2", friend, id)
+        .execute(&db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("friend request"));
+    }
+
+    Ok("Declined friend request!")
+}
+
+pub async fn remove_friend(
+    key: ApiKey,
+    Path((id, friend)): Path<(Uuid, Uuid)>,
+    State(db): State<PgPool>,
+) -> Result<&'static str> {
+    key.require(Scope::Pay)?;
+
+    let mut tx = db.begin().await?;
+
+    let result = sqlx::query!(
+        "DELETE FROM friend WHERE (friend_id = $1 AND person_id = $2) OR (friend_id = $2 AND person_id = $1)",
+        friend,
+        id
+    )
+    .execute(&mut tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("friend"));
+    }
+
+    reset_account_in_use(id, friend, &mut tx).await?;
+    reset_account_in_use(friend, id, &mut tx).await?;
+
+    tx.commit().await?;
+
+    Ok("Removed friend!")
+}
+
+/// Allows or disallows a friend to spend from your account
+pub async fn set_friend_accountant(
+    key: ApiKey,
+    Path((id, friend)): Path<(Uuid, Uuid)>,
+    Query(params): Query<FriendAccountantParams>,
+    State(db): State<PgPool>,
+) -> Result<&'static str> {
+    key.require(Scope::Pay)?;
+
+    let mut tx = db.begin().await?;
+
+    let result = sqlx::query!(
+        "UPDATE friend SET accountant = $3 WHERE friend_id = $1 AND person_id = $2",
+        friend,
+        id,
+        params.accountant
+    )
+    .execute(&mut tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::NotFound("friend"));
+    }
+
+    if !params.accountant {
+        reset_account_in_use(friend, id, &mut tx).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok("Updated friend!")
+}
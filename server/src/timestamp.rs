@@ -1,13 +1,85 @@
+use shared::error::ErrorCode;
 use std::fmt;
-use uuid::Timestamp;
+use uuid::{NoContext, Timestamp};
 use uuid::Uuid;
 
+use crate::error::Error;
+use crate::error::Result;
+
+/// The latest second a v7 uuid can hold, its timestamp is 48 bits of milliseconds
+const MAX_UNIX_SEC: u64 = ((1 << 48) - 1) / 1000;
+
 pub struct UUIDTime(Timestamp);
 
 impl UUIDTime {
-    fn to_unix_sec(&self) -> u64 {
+    /// Fails for times too far in the future to be stored in a v7 uuid
+    pub fn from_unix_sec(seconds: u64) -> Result<Self> {
+        if seconds > MAX_UNIX_SEC {
+            return Err(Error::HttpError(ErrorCode::InvalidInput, "Timestamps cannot be that far in the future"));
+        }
+
+        Ok(UUIDTime(Timestamp::from_unix(NoContext, seconds, 0)))
+    }
+
+    #[must_use]
+    pub fn to_unix_sec(&self) -> u64 {
         self.0.to_unix().0
     }
+
+    fn to_unix_millis(&self) -> u64 {
+        let (seconds, nanos) = self.0.to_unix();
+        seconds * 1000 + u64::from(nanos / 1_000_000)
+    }
+
+    /// The lowest v7 uuid that could be generated at this time
+    #[must_use]
+    pub fn first_uuid(&self) -> Uuid {
+        v7_bound(self.to_unix_millis(), 0x00)
+    }
+
+    /// The highest v7 uuid that could be generated at this time
+    #[must_use]
+    pub fn last_uuid(&self) -> Uuid {
+        v7_bound(self.to_unix_millis(), 0xFF)
+    }
+}
+
+/// The range of v7 ids a page covers, `since` is inclusive while `until`
+/// and the cursor left by the previous page are exclusive
+pub fn page_bounds(since: Option<u64>, until: Option<u64>, cursor: Option<Uuid>) -> Result<(Uuid, Uuid)> {
+    let lower = match since {
+        Some(since) => UUIDTime::from_unix_sec(since)?.first_uuid(),
+        None => Uuid::nil(),
+    };
+    let until = until.map(UUIDTime::from_unix_sec).transpose()?.map(|until| until.first_uuid());
+    let upper = match (cursor, until) {
+        (Some(cursor), Some(until)) => cursor.min(until),
+        (Some(bound), None) | (None, Some(bound)) => bound,
+        (None, None) => Uuid::from_bytes([0xFF; 16]),
+    };
+
+    Ok((lower, upper))
+}
+
+/// Cuts a page fetched with one entry more than `limit` down to size,
+/// returning the cursor of the next page if there is one
+pub fn next_cursor<T>(entries: &mut Vec<T>, limit: i64, id: impl Fn(&T) -> Uuid) -> Option<Uuid> {
+    let limit = usize::try_from(limit).unwrap_or_default();
+
+    if entries.len() > limit {
+        entries.truncate(limit);
+        entries.last().map(id)
+    } else {
+        None
+    }
+}
+
+fn v7_bound(millis: u64, fill: u8) -> Uuid {
+    let mut bytes = [fill; 16];
+    bytes[..6].copy_from_slice(&millis.to_be_bytes()[2..]);
+    bytes[6] = 0x70 | (fill & 0x0F);
+    bytes[8] = 0x80 | (fill & 0x3F);
+    Uuid::from_bytes(bytes)
 }
 
 impl fmt::Display for UUIDTime {
@@ -0,0 +1,34 @@
+use shared::error::ErrorCode;
+
+use crate::error::Error;
+use crate::error::Result;
+
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_REASON_LENGTH: usize = 256;
+const MAX_CATEGORY_LENGTH: usize = 32;
+
+/// Trims `value`, failing with `message` if nothing is left or it's longer than `max_length` characters
+fn trimmed<'a>(value: &'a str, max_length: usize, message: &'static str) -> Result<&'a str> {
+    let value = value.trim();
+
+    if value.is_empty() || value.chars().count() > max_length {
+        return Err(Error::HttpError(ErrorCode::InvalidInput, message));
+    }
+
+    Ok(value)
+}
+
+/// Names are shown to people in place of uuids so they have to be readable
+pub fn validate_name(name: &str) -> Result<&str> {
+    trimmed(name, MAX_NAME_LENGTH, "Names must be between 1 and 32 characters")
+}
+
+/// Reasons end up in the transaction log so they can't be empty
+pub fn validate_reason(reason: &str) -> Result<&str> {
+    trimmed(reason, MAX_REASON_LENGTH, "A reason of at most 256 characters is required")
+}
+
+/// Categories are compared case-insensitively so they get stored lowercase
+pub fn validate_category(category: &str) -> Result<String> {
+    trimmed(category, MAX_CATEGORY_LENGTH, "Categories must be between 1 and 32 characters").map(str::to_lowercase)
+}
@@ -2,6 +2,7 @@
 
 use async_trait::async_trait;
 use reqwest::{Response, StatusCode};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use reqwest::Error as ReqwestError;
@@ -24,6 +25,43 @@ pub enum Error {
     #[error("Failed at parsing money from a string")]
     MoneyFromString(#[from] MoneyFromStringError),
 
+    #[error("{0}")]
+    Unauthorized(String),
+
+    #[error("{0}")]
+    Forbidden(String),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("{0}")]
+    InsufficientFunds(String),
+
+    #[error("{0}")]
+    InvalidAmount(String),
+
+    #[error("{0}")]
+    InvalidInput(String),
+
+    #[error("{0}")]
+    InvalidOperation(String),
+
+    #[error("{0}")]
+    AlreadyExists(String),
+
+    #[error("{0}")]
+    AlreadyRegistered(String),
+
+    #[error("{0}")]
+    AlreadyReversed(String),
+
+    #[error("{0}")]
+    NameTaken(String),
+
+    #[error("{0}")]
+    IdempotencyConflict(String),
+
+    /// A failed response without a known error code
     #[error("{code} Response: {message}")]
     FailedResponse { code: StatusCode, message: String },
 
@@ -40,6 +78,57 @@ pub enum MoneyFromStringError {
     ValueTooPrecise,
 }
 
+/// Machine readable reason a request failed, these never change meaning once added
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    InsufficientFunds,
+    InvalidAmount,
+    InvalidInput,
+    InvalidOperation,
+    AlreadyExists,
+    AlreadyRegistered,
+    AlreadyReversed,
+    NameTaken,
+    IdempotencyConflict,
+    Internal,
+    /// Codes added to the server after this client was built
+    #[serde(other)]
+    Unknown,
+}
+
+/// The body of every failed response
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ErrorBody {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl Error {
+    fn from_body(status: StatusCode, body: ErrorBody) -> Error {
+        let message = body.message;
+
+        match body.code {
+            ErrorCode::Unauthorized => Error::Unauthorized(message),
+            ErrorCode::Forbidden => Error::Forbidden(message),
+            ErrorCode::NotFound => Error::NotFound(message),
+            ErrorCode::InsufficientFunds => Error::InsufficientFunds(message),
+            ErrorCode::InvalidAmount => Error::InvalidAmount(message),
+            ErrorCode::InvalidInput => Error::InvalidInput(message),
+            ErrorCode::InvalidOperation => Error::InvalidOperation(message),
+            ErrorCode::AlreadyExists => Error::AlreadyExists(message),
+            ErrorCode::AlreadyRegistered => Error::AlreadyRegistered(message),
+            ErrorCode::AlreadyReversed => Error::AlreadyReversed(message),
+            ErrorCode::NameTaken => Error::NameTaken(message),
+            ErrorCode::IdempotencyConflict => Error::IdempotencyConflict(message),
+            ErrorCode::Internal | ErrorCode::Unknown => Error::FailedResponse { code: status, message },
+        }
+    }
+}
+
 #[async_trait]
 pub trait FailedResponseError<T> {
     async fn verify_success(self) -> Result<T>;
@@ -48,13 +137,27 @@ pub trait FailedResponseError<T> {
 #[async_trait]
 impl FailedResponseError<Response> for Response {
     async fn verify_success(self) -> Result<Response> {
-        if !self.status().is_success() {
-            return Err(Error::FailedResponse {
-                code: self.status(),
-                message: self.text().await?,
+        let status = self.status();
+
+        if !status.is_success() {
+            let text = self.text().await?;
+
+            // Errors from outside the handlers, like malformed queries, are plain text
+            return Err(match serde_json::from_str(&text) {
+                Ok(body) => Error::from_body(status, body),
+                Err(_) => Error::FailedResponse { code: status, message: text },
             });
         }
 
         Ok(self)
     }
 }
+
+#[test]
+fn error_body() {
+    let body: ErrorBody = serde_json::from_str(r#"{"code":"insufficient_funds","message":"Too poor"}"#).unwrap();
+    assert!(matches!(Error::from_body(StatusCode::BAD_REQUEST, body), Error::InsufficientFunds(message) if message == "Too poor"));
+
+    let body: ErrorBody = serde_json::from_str(r#"{"code":"from_the_future","message":"?"}"#).unwrap();
+    assert_eq!(body.code, ErrorCode::Unknown);
+}
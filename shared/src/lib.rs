@@ -1,7 +1,8 @@
 #![warn(clippy::all, clippy::pedantic)]
 #![allow(clippy::missing_errors_doc)]
 
-use reqwest::Client;
+use reqwest::{Client, IntoUrl, RequestBuilder};
+use std::fmt::Write;
 
 pub mod error;
 pub mod money;
@@ -11,31 +12,58 @@ pub mod types;
 pub struct AekosiaAPI {
     pub client: Client,
     pub website_url: String,
+    api_key: String,
     eco_balance: String,
     eco_payment: String,
     eco_print: String,
+    eco_burn: String,
+    eco_history: String,
+    eco_transaction: String,
+    eco_leaderboard: String,
+    eco_stats: String,
     person_register: String,
     person_get: String,
-    person_get_discord: String
+    person_get_discord: String,
+    admin_keys: String,
+    admin_reconcile: String,
+    admin_audit: String,
+    account_get: String,
+    account_by_name: String,
+    org_create: String,
+    org_get: String,
 }
 
 const UUID: usize = 32;
 const I64: usize = 20;
 const U64: usize = 19;
+const NAME: usize = 32;
 
 impl AekosiaAPI {
     #[allow(clippy::must_use_candidate)]
-    pub fn new(client: Client, website_url: &str) -> AekosiaAPI {
+    pub fn new(client: Client, website_url: &str, api_key: &str) -> AekosiaAPI {
         let website_url = website_url.trim_end_matches('/').to_string();
 
         AekosiaAPI {
             client,
+            api_key: api_key.to_string(),
             eco_balance: format_with_query(&website_url, "/eco/balance/", UUID),
             eco_payment: format!("{website_url}/eco/payment"),
             eco_print: format_with_query(&website_url, "/eco/print/", UUID + 1 + I64),
+            eco_burn: format_with_query(&website_url, "/eco/burn/", UUID + 1 + I64),
+            eco_history: format_with_query(&website_url, "/eco/history/", UUID),
+            eco_transaction: format_with_query(&website_url, "/eco/transaction/", UUID + 8),
+            eco_leaderboard: format!("{website_url}/eco/leaderboard"),
+            eco_stats: format!("{website_url}/eco/stats"),
             person_register: format_with_query(&website_url, "/person/register/", I64),
             person_get: format_with_query(&website_url, "/person/", UUID),
             person_get_discord: format_with_query(&website_url, "/person/from_discord/", U64),
+            admin_keys: format_with_query(&website_url, "/admin/keys", 1 + UUID),
+            admin_reconcile: format!("{website_url}/admin/reconcile"),
+            admin_audit: format!("{website_url}/admin/audit"),
+            account_get: format_with_query(&website_url, "/account/", UUID),
+            account_by_name: format_with_query(&website_url, "/account/by_name/", NAME),
+            org_create: format!("{website_url}/org/create"),
+            org_get: format_with_query(&website_url, "/org/", UUID + 9 + UUID),
 
             website_url,
         }
@@ -43,7 +71,25 @@ impl AekosiaAPI {
 
     #[cfg(test)]
     pub(crate) fn new_test() -> AekosiaAPI {
-        AekosiaAPI::new(Client::new(), "http://127.0.0.1:8080")
+        let api_key = std::env::var("API_KEY").unwrap_or_default();
+
+        AekosiaAPI::new(Client::new(), "http://127.0.0.1:8080", &api_key)
+    }
+
+    fn get(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.client.get(url).bearer_auth(&self.api_key)
+    }
+
+    fn post(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.client.post(url).bearer_auth(&self.api_key)
+    }
+
+    fn put(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.client.put(url).bearer_auth(&self.api_key)
+    }
+
+    fn delete(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.client.delete(url).bearer_auth(&self.api_key)
     }
 }
 
@@ -53,3 +99,24 @@ fn format_with_query(website_url: &str, path: &'static str, query_type: usize) -
     string.push_str(path);
     string
 }
+
+/// Percent encodes everything but unreserved characters so user input can be put into a path
+fn encode_path_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            // Writing to a string cannot fail
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+    }
+
+    encoded
+}
+
+#[test]
+fn encode_path() {
+    assert_eq!(encode_path_segment("Guild Treasury/1"), "Guild%20Treasury%2F1");
+}
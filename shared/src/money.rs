@@ -1,3 +1,6 @@
+// Newer clippy prefers std::sync::LazyLock over once_cell
+#![allow(clippy::non_std_lazy_statics)]
+
 use crate::error::MoneyFromStringError;
 use once_cell::sync::Lazy;
 use std::ops::{Add, Deref, DerefMut};
@@ -54,6 +57,7 @@ impl Sub<i64> for Money {
 }
 
 const PRECISION: usize = 3;
+const SCALE: f64 = 1000_f64;
 const STRING_LENGTH: usize = 2 + PRECISION;
 const NEG_STRING_LENGTH: usize = STRING_LENGTH + 1;
 
@@ -126,7 +130,7 @@ impl fmt::Display for Money {
             _ => {
                 string = self.0.to_string();
                 let length = string.len();
-                if string.ends_with("00") {
+                if string.ends_with("000") {
                     &string[0..length - PRECISION]
                 } else {
                     string.insert(length - PRECISION, '.');
@@ -149,11 +153,11 @@ impl FromStr for Money {
     type Err = MoneyFromStringError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if !matches!(s.find('.'), Some(index) if index >= s.len() - PRECISION) {
+        if matches!(s.find('.'), Some(index) if s.len() - index - 1 > PRECISION) {
             return Err(MoneyFromStringError::ValueTooPrecise);
         }
 
-        let parsed = s.parse::<f64>()? * 100_f64;
+        let parsed = (s.parse::<f64>()? * SCALE).round();
 
         #[allow(clippy::cast_possible_truncation)]
         Ok(Money(parsed as i64))
@@ -170,6 +174,11 @@ fn to_str_decimal() {
     assert_eq!(Money(4242).to_string(), "4.242");
 }
 
+#[test]
+fn to_str_trailing_zeros() {
+    assert_eq!(Money(1500).to_string(), "1.500");
+}
+
 #[test]
 fn to_str_zero() {
     assert_eq!(Money(0).to_string(), "0.000");
@@ -237,10 +246,15 @@ fn to_str_triple_neg_digit() {
 
 #[test]
 fn from_str() {
-    assert_eq!(Money::from_str("42.50").unwrap(), Money(4250));
+    assert_eq!(Money::from_str("42.50").unwrap(), Money(42500));
+}
+
+#[test]
+fn from_str_integer() {
+    assert_eq!(Money::from_str("5").unwrap(), Money(5000));
 }
 
 #[test]
 fn from_str_err() {
-    assert_eq!(Money::from_str("42.507").unwrap_err(), MoneyFromStringError::ValueTooPrecise);
+    assert_eq!(Money::from_str("42.5075").unwrap_err(), MoneyFromStringError::ValueTooPrecise);
 }
\ No newline at end of file
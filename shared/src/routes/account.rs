@@ -0,0 +1,75 @@
+use uuid::Uuid;
+
+use crate::error::{FailedResponseError, Result};
+use crate::types::{AccountDetailsParams, AccountInfo};
+use crate::{encode_path_segment, AekosiaAPI};
+
+impl AekosiaAPI {
+    pub async fn get_account(&self, id: &Uuid) -> Result<AccountInfo> {
+        let resp = self
+            .get(self.account_get.clone() + &id.as_simple().to_string())
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    /// Names are matched case insensitively
+    pub async fn get_account_by_name(&self, name: &str) -> Result<AccountInfo> {
+        let resp = self
+            .get(self.account_by_name.clone() + &encode_path_segment(name))
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    pub async fn set_account_details(&self, id: &Uuid, params: &AccountDetailsParams) -> Result<()> {
+        self
+            .put(self.account_get.clone() + &id.as_simple().to_string())
+            .query(params)
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use color_eyre::eyre::Result;
+    use tokio;
+    use uuid::uuid;
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn account_names() -> Result<()> {
+        let client = AekosiaAPI::new_test();
+        let id = uuid!("01844a37-ec48-7579-86bd-0cdb39f6cd24");
+
+        client
+            .set_account_details(
+                &id,
+                &AccountDetailsParams {
+                    actor: id,
+                    name: Some("Test Treasury".to_string()),
+                    description: Some("For testing".to_string()),
+                },
+            )
+            .await?;
+
+        let account = client.get_account_by_name("test treasury").await?;
+        assert_eq!(account.id, id);
+        assert_eq!(account.name, "Test Treasury");
+        assert_eq!(account.description, "For testing");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use uuid::Uuid;
+
+use crate::error::{FailedResponseError, Result};
+use crate::types::{ApiKeyInfo, ApiKeyParams, AuditPage, AuditParams, CreatedApiKey, ReconcileParams, ReconcileReport};
+use crate::AekosiaAPI;
+
+impl AekosiaAPI {
+    pub async fn create_api_key(&self, params: &ApiKeyParams) -> Result<CreatedApiKey> {
+        let resp = self
+            .post(&self.admin_keys)
+            .query(params)
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    pub async fn list_api_keys(&self) -> Result<Vec<ApiKeyInfo>> {
+        let resp = self
+            .get(&self.admin_keys)
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    pub async fn revoke_api_key(&self, id: &Uuid) -> Result<()> {
+        self
+            .delete(format!("{}/{}", self.admin_keys, id.as_simple()))
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Compares stored balances to the ledger, `repair` overwrites them with what the ledger implies
+    pub async fn reconcile(&self, repair: bool) -> Result<ReconcileReport> {
+        let resp = self
+            .post(&self.admin_reconcile)
+            .query(&ReconcileParams { repair: Some(repair) })
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    /// Finds who changed what, newest first
+    pub async fn get_audit_log(&self, params: &AuditParams) -> Result<AuditPage> {
+        let resp = self
+            .get(&self.admin_audit)
+            .query(params)
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use color_eyre::eyre::Result;
+    use tokio;
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn create_and_revoke_api_key() -> Result<()> {
+        let client = AekosiaAPI::new_test();
+
+        let created = client
+            .create_api_key(&ApiKeyParams {
+                name: "test".to_string(),
+                read: true,
+                ..Default::default()
+            })
+            .await?;
+
+        client.revoke_api_key(&created.id).await?;
+
+        let revoked = client
+            .list_api_keys()
+            .await?
+            .into_iter()
+            .find(|key| key.id == created.id)
+            .unwrap();
+
+        assert!(revoked.revoked);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn reconcile() -> Result<()> {
+        let report = AekosiaAPI::new_test().reconcile(false).await?;
+
+        assert!(report.mismatches.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn get_audit_log() -> Result<()> {
+        let client = AekosiaAPI::new_test();
+
+        client.reconcile(false).await?;
+
+        let page = client
+            .get_audit_log(&AuditParams {
+                action: Some("POST /admin/reconcile".to_string()),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await?;
+
+        assert_eq!(page.entries[0].parameters.get("repair").map(String::as_str), Some("false"));
+
+        Ok(())
+    }
+}
@@ -2,13 +2,14 @@ use uuid::Uuid;
 
 use crate::error::{FailedResponseError, Result};
 use crate::money::Money;
-use crate::types::PaymentParamsReferences;
+use crate::types::{BurnParams, EconomyStats, HistoryPage, HistoryParams, Leaderboard, LeaderboardParams, PaymentParamsReferences, PrintParams, Receipt, ReverseParams};
 use crate::AekosiaAPI;
 
+const PAYMENT_ATTEMPTS: u32 = 3;
+
 impl AekosiaAPI {
     pub async fn get_balance(&self, id: &Uuid) -> Result<Money> {
         let resp = self
-            .client
             .get(self.eco_balance.clone() + &id.as_simple().to_string())
             .send()
             .await?
@@ -18,35 +19,117 @@ impl AekosiaAPI {
         Ok(Money(resp.text().await?.parse()?))
     }
 
-    pub async fn print_money(&self, id: &Uuid, amount: &Money) -> Result<()> {
-        self
-            .client
-            .post(format!("{}{}/{}", self.eco_print, id.as_simple(), *amount))
+    /// Prints money from the mint, the reason is kept in the transaction log
+    pub async fn print_money(&self, id: &Uuid, amount: &Money, reason: &str) -> Result<Receipt> {
+        let resp = self
+            .post(format!("{}{}/{}", self.eco_print, id.as_simple(), amount.0))
+            .query(&PrintParams { reason: reason.to_string() })
             .send()
             .await?
             .verify_success()
             .await?;
 
-        Ok(())
+        Ok(resp.json().await?)
+    }
+
+    /// Burns money by sending it to the burn account, needs a key that can print
+    pub async fn burn_money(&self, id: &Uuid, amount: &Money, reason: &str) -> Result<Receipt> {
+        let resp = self
+            .post(format!("{}{}/{}", self.eco_burn, id.as_simple(), amount.0))
+            .query(&BurnParams { reason: reason.to_string() })
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
     }
 
-    pub async fn payment(&self, from: &Uuid, to: &Uuid, amount: &Money) -> Result<()> {
+    /// Pays from the account the actor is currently using if `from` is `None`.
+    /// Requests that time out are retried without the risk of paying twice.
+    pub async fn payment(
+        &self,
+        actor: &Uuid,
+        from: Option<&Uuid>,
+        to: &Uuid,
+        amount: &Money,
+        memo: Option<&str>,
+        category: Option<&str>,
+    ) -> Result<Receipt> {
+        let idempotency_key = Uuid::new_v4();
+
         let params = PaymentParamsReferences {
+            actor,
             to,
             from,
             amount: &amount.0,
-            force: None,
+            memo,
+            category,
+            admin_override: None,
+            idempotency_key: Some(&idempotency_key),
         };
 
-        self.client
-            .post(&self.eco_payment)
-            .query(&params)
+        let mut attempts = 0;
+
+        let resp = loop {
+            attempts += 1;
+
+            match self.post(&self.eco_payment).query(&params).send().await {
+                Err(err) if attempts < PAYMENT_ATTEMPTS && (err.is_timeout() || err.is_connect()) => {}
+                result => break result?,
+            }
+        };
+
+        Ok(resp.verify_success().await?.json().await?)
+    }
+
+    pub async fn get_history(&self, id: &Uuid, params: &HistoryParams) -> Result<HistoryPage> {
+        let resp = self
+            .get(self.eco_history.clone() + &id.as_simple().to_string())
+            .query(params)
             .send()
             .await?
             .verify_success()
             .await?;
 
-        Ok(())
+        Ok(resp.json().await?)
+    }
+
+    /// People ranked by the balance of their personal account, richest first
+    pub async fn leaderboard(&self, params: &LeaderboardParams) -> Result<Leaderboard> {
+        let resp = self
+            .get(&self.eco_leaderboard)
+            .query(params)
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    pub async fn get_stats(&self) -> Result<EconomyStats> {
+        let resp = self
+            .get(&self.eco_stats)
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    /// Sends the money of a past payment back, all that is left of it unless an amount is given
+    pub async fn reverse(&self, id: &Uuid, params: &ReverseParams) -> Result<Receipt> {
+        let resp = self
+            .post(format!("{}{}/reverse", self.eco_transaction, id.as_simple()))
+            .query(params)
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
     }
 }
 
@@ -72,10 +155,24 @@ mod tests {
     #[tokio::test]
     #[ignore = "Need a running server with the right conditions to run these!"]
     async fn print_money() -> Result<()> {
-        AekosiaAPI::new_test()
-            .print_money(&uuid!("01844ffb50ee7275af11e47e51bc92e7"), &Money(10))
+        let receipt = AekosiaAPI::new_test()
+            .print_money(&uuid!("01844ffb50ee7275af11e47e51bc92e7"), &Money(10), "Testing")
             .await?;
 
+        assert_eq!(receipt.from, crate::types::MINT_ACCOUNT);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn burn_money() -> Result<()> {
+        let receipt = AekosiaAPI::new_test()
+            .burn_money(&uuid!("01844ffb50ee7275af11e47e51bc92e7"), &Money(10), "Testing")
+            .await?;
+
+        assert_eq!(receipt.to, crate::types::BURN_ACCOUNT);
+
         Ok(())
     }
 
@@ -91,11 +188,14 @@ mod tests {
             .get_balance(&uuid!("01844b8a-0108-76e7-8bd6-049df41106cd"))
             .await?;
 
-        client
+        let receipt = client
             .payment(
                 &uuid!("01844a37-ec48-7579-86bd-0cdb39f6cd24"),
+                Some(&uuid!("01844a37-ec48-7579-86bd-0cdb39f6cd24")),
                 &uuid!("01844b8a-0108-76e7-8bd6-049df41106cd"),
                 &Money(10),
+                Some("Testing"),
+                Some("tests"),
             )
             .await?;
 
@@ -108,6 +208,79 @@ mod tests {
 
         assert_eq!(before_from - 10, after_from);
         assert_eq!(before_to + 10, after_to);
+        assert_eq!(receipt.from_balance, *after_from);
+        assert_eq!(receipt.to_balance, *after_to);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn reverse() -> Result<()> {
+        let client = AekosiaAPI::new_test();
+        let from = uuid!("01844a37-ec48-7579-86bd-0cdb39f6cd24");
+        let to = uuid!("01844b8a-0108-76e7-8bd6-049df41106cd");
+
+        let receipt = client.payment(&from, Some(&from), &to, &Money(10), None, None).await?;
+
+        let params = ReverseParams { actor: to, amount: Some(4), ..Default::default() };
+        let reversal = client.reverse(&receipt.id, &params).await?;
+
+        assert_eq!((reversal.from, reversal.to, reversal.amount), (to, from, 4));
+        assert!(client.reverse(&receipt.id, &params).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn get_history() -> Result<()> {
+        let client = AekosiaAPI::new_test();
+        let id = uuid!("01844a37-ec48-7579-86bd-0cdb39f6cd24");
+
+        let first = client
+            .get_history(&id, &HistoryParams { limit: Some(1), ..Default::default() })
+            .await?;
+
+        assert!(first.entries.len() <= 1);
+        assert!(first
+            .entries
+            .iter()
+            .all(|entry| entry.postings.iter().map(|posting| posting.amount).sum::<i64>() == 0));
+
+        if let Some(cursor) = first.next_cursor {
+            let second = client
+                .get_history(&id, &HistoryParams { cursor: Some(cursor), limit: Some(1), ..Default::default() })
+                .await?;
+
+            assert!(second.entries.iter().all(|entry| entry.id < cursor));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn leaderboard() -> Result<()> {
+        let person = uuid!("01844a37-ec48-7579-86bd-0cdb39f6cd24");
+
+        let leaderboard = AekosiaAPI::new_test()
+            .leaderboard(&LeaderboardParams { limit: Some(5), person: Some(person), ..Default::default() })
+            .await?;
+
+        assert!(leaderboard.entries.windows(2).all(|pair| pair[0].balance >= pair[1].balance));
+        assert_eq!(leaderboard.person.map(|entry| entry.id), Some(person));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn get_stats() -> Result<()> {
+        let stats = AekosiaAPI::new_test().get_stats().await?;
+
+        assert_eq!(stats.money_supply, stats.minted - stats.burned);
+        assert!((0.0..=1.0).contains(&stats.gini));
 
         Ok(())
     }
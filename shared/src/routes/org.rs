@@ -0,0 +1,146 @@
+use uuid::Uuid;
+
+use crate::error::{FailedResponseError, Result};
+use crate::types::{ActorParams, CreateOrganisationParams, MemberFlagsParams, OrganisationInfo};
+use crate::AekosiaAPI;
+
+impl AekosiaAPI {
+    pub async fn create_organisation(&self, owner: &Uuid, name: Option<&str>, public: bool) -> Result<Uuid> {
+        let params = CreateOrganisationParams {
+            owner: *owner,
+            name: name.map(ToString::to_string),
+            public: Some(public),
+        };
+
+        let resp = self
+            .post(&self.org_create)
+            .query(&params)
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(Uuid::parse_str(&resp.text().await?)?)
+    }
+
+    pub async fn get_organisation(&self, id: &Uuid) -> Result<OrganisationInfo> {
+        let resp = self
+            .get(self.org_get.clone() + &id.as_simple().to_string())
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    pub async fn invite_member(&self, organisation: &Uuid, actor: &Uuid, member: &Uuid) -> Result<()> {
+        self
+            .post(format!("{}{}/invite/{}", self.org_get, organisation.as_simple(), member.as_simple()))
+            .query(&ActorParams { actor: *actor })
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_member(&self, organisation: &Uuid, actor: &Uuid, member: &Uuid) -> Result<()> {
+        self
+            .delete(format!("{}{}/members/{}", self.org_get, organisation.as_simple(), member.as_simple()))
+            .query(&ActorParams { actor: *actor })
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_member_flags(&self, organisation: &Uuid, member: &Uuid, flags: &MemberFlagsParams) -> Result<()> {
+        self
+            .put(format!("{}{}/members/{}", self.org_get, organisation.as_simple(), member.as_simple()))
+            .query(flags)
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Money;
+    use color_eyre::eyre::Result;
+    use tokio;
+    use uuid::uuid;
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn manage_organisation() -> Result<()> {
+        let client = AekosiaAPI::new_test();
+        let owner = uuid!("01844a37-ec48-7579-86bd-0cdb39f6cd24");
+        let member = uuid!("01844b8a-0108-76e7-8bd6-049df41106cd");
+
+        let organisation = client.create_organisation(&owner, None, false).await?;
+
+        client.invite_member(&organisation, &owner, &member).await?;
+        client
+            .set_member_flags(
+                &organisation,
+                &member,
+                &MemberFlagsParams {
+                    actor: owner,
+                    accountant: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        let info = client.get_organisation(&organisation).await?;
+        assert_eq!(info.owner, owner);
+        assert!(info.members.iter().any(|m| m.id == member && m.accountant));
+
+        client.remove_member(&organisation, &owner, &member).await?;
+
+        let info = client.get_organisation(&organisation).await?;
+        assert!(info.members.iter().all(|m| m.id != member));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn accountant_payment() -> Result<()> {
+        let client = AekosiaAPI::new_test();
+        let owner = uuid!("01844a37-ec48-7579-86bd-0cdb39f6cd24");
+        let member = uuid!("01844b8a-0108-76e7-8bd6-049df41106cd");
+
+        let organisation = client.create_organisation(&owner, None, false).await?;
+        client.print_money(&organisation, &Money(10), "Testing").await?;
+        client.invite_member(&organisation, &owner, &member).await?;
+
+        assert!(client.payment(&member, Some(&organisation), &member, &Money(5), None, None).await.is_err());
+
+        client
+            .set_member_flags(
+                &organisation,
+                &member,
+                &MemberFlagsParams {
+                    actor: owner,
+                    accountant: Some(true),
+                    ..Default::default()
+                },
+            )
+            .await?;
+
+        client.payment(&member, Some(&organisation), &member, &Money(5), None, None).await?;
+        assert_eq!(client.get_balance(&organisation).await?, Money(5));
+
+        Ok(())
+    }
+}
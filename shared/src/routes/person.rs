@@ -3,13 +3,12 @@ use std::str::FromStr;
 use uuid::Uuid;
 
 use crate::error::{FailedResponseError, Result};
-use crate::types::PersonInfo;
+use crate::types::{ActiveBalance, FriendAccountantParams, FriendInfo, FriendRequests, PersonInfo};
 use crate::AekosiaAPI;
 
 impl AekosiaAPI {
     pub async fn get_person(&self, id: &Uuid) -> Result<PersonInfo> {
         let resp = self
-            .client
             .get(self.person_get.clone() + &id.as_simple().to_string())
             .send()
             .await?
@@ -21,7 +20,6 @@ impl AekosiaAPI {
 
     pub async fn get_id_from_discord(&self, id: &u64) -> Result<Uuid> {
         let resp = self
-            .client
             .get(self.person_get_discord.clone() + &id.to_string())
             .send()
             .await?
@@ -33,7 +31,6 @@ impl AekosiaAPI {
 
     pub async fn register_person(&self, discord_id: &u64) -> Result<Uuid> {
         let res = self
-            .client
             .post(self.person_register.clone() + &discord_id.to_string())
             .send()
             .await?
@@ -42,6 +39,98 @@ impl AekosiaAPI {
 
         Ok(Uuid::parse_str(&res.text().await?)?)
     }
+
+    /// Gets the balance of the account the person is currently using
+    pub async fn get_active_balance(&self, id: &Uuid) -> Result<ActiveBalance> {
+        let resp = self
+            .get(format!("{}{}/balance", self.person_get, id.as_simple()))
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    pub async fn set_account_in_use(&self, id: &Uuid, account: &Uuid) -> Result<()> {
+        self
+            .put(format!("{}{}/account_in_use/{}", self.person_get, id.as_simple(), account.as_simple()))
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_friends(&self, id: &Uuid) -> Result<Vec<FriendInfo>> {
+        let resp = self
+            .get(format!("{}{}/friends", self.person_get, id.as_simple()))
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    pub async fn get_friend_requests(&self, id: &Uuid) -> Result<FriendRequests> {
+        let resp = self
+            .get(format!("{}{}/friends/requests", self.person_get, id.as_simple()))
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(resp.json().await?)
+    }
+
+    pub async fn send_friend_request(&self, id: &Uuid, friend: &Uuid) -> Result<()> {
+        self.friend_action(id, friend, "request").await
+    }
+
+    pub async fn accept_friend_request(&self, id: &Uuid, friend: &Uuid) -> Result<()> {
+        self.friend_action(id, friend, "accept").await
+    }
+
+    pub async fn decline_friend_request(&self, id: &Uuid, friend: &Uuid) -> Result<()> {
+        self.friend_action(id, friend, "decline").await
+    }
+
+    async fn friend_action(&self, id: &Uuid, friend: &Uuid, action: &str) -> Result<()> {
+        self
+            .post(format!("{}{}/friends/{}/{action}", self.person_get, id.as_simple(), friend.as_simple()))
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove_friend(&self, id: &Uuid, friend: &Uuid) -> Result<()> {
+        self
+            .delete(format!("{}{}/friends/{}", self.person_get, id.as_simple(), friend.as_simple()))
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(())
+    }
+
+    /// Allows or disallows `friend` to spend from the account of `id`
+    pub async fn set_friend_accountant(&self, id: &Uuid, friend: &Uuid, accountant: bool) -> Result<()> {
+        self
+            .put(format!("{}{}/friends/{}", self.person_get, id.as_simple(), friend.as_simple()))
+            .query(&FriendAccountantParams { accountant })
+            .send()
+            .await?
+            .verify_success()
+            .await?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -49,7 +138,6 @@ mod tests {
     use super::*;
     use crate::error::Error;
     use color_eyre::eyre::Result;
-    use reqwest::StatusCode;
     use tokio;
     use uuid::uuid;
 
@@ -63,7 +151,10 @@ mod tests {
         assert_eq!(
             PersonInfo {
                 discord_id: 153_555_060_926_840_833,
-                balance: 0
+                name: "01844ffb50ee7275af11e47e51bc92e7".to_string(),
+                description: String::new(),
+                balance: 0,
+                account_in_use: uuid!("01844ffb50ee7275af11e47e51bc92e7"),
             },
             a
         );
@@ -85,8 +176,7 @@ mod tests {
         _ = AekosiaAPI::new_test().register_person(&1000).await?;
         let err = AekosiaAPI::new_test().register_person(&900).await;
 
-        if let Err(Error::FailedResponse { code, message }) = err {
-            assert_eq!(code, StatusCode::from_u16(400)?);
+        if let Err(Error::AlreadyRegistered(message)) = err {
             assert_eq!(message, "This person is already registered!");
         } else {
             err?;
@@ -95,4 +185,26 @@ mod tests {
         
         Ok(())
     }
+
+    #[tokio::test]
+    #[ignore = "Need a running server with the right conditions to run these!"]
+    async fn friends() -> Result<()> {
+        let client = AekosiaAPI::new_test();
+        let person = uuid!("01844a37-ec48-7579-86bd-0cdb39f6cd24");
+        let friend = uuid!("01844b8a-0108-76e7-8bd6-049df41106cd");
+
+        client.send_friend_request(&person, &friend).await?;
+        assert_eq!(client.get_friend_requests(&friend).await?.incoming[0].id, person);
+
+        client.accept_friend_request(&friend, &person).await?;
+        client.set_friend_accountant(&person, &friend, true).await?;
+
+        let friends = client.get_friends(&person).await?;
+        assert!(friends.iter().any(|info| info.id == friend && info.accountant));
+
+        client.remove_friend(&friend, &person).await?;
+        assert!(client.get_friends(&person).await?.is_empty());
+
+        Ok(())
+    }
 }
@@ -1,24 +1,357 @@
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use std::collections::BTreeMap;
+use uuid::{uuid, Uuid};
+
+/// Printed money is paid from this account, its balance is the negative of all money ever printed
+pub const MINT_ACCOUNT: Uuid = uuid!("00000000-0000-7000-8000-000000000001");
+
+/// Money taken out of circulation is paid to this account
+pub const BURN_ACCOUNT: Uuid = uuid!("00000000-0000-7000-8000-000000000002");
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct PersonInfo {
     pub discord_id: u64,
+    pub name: String,
+    pub description: String,
+    pub balance: i64,
+    pub account_in_use: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ActiveBalance {
+    /// The account the person is currently using
+    pub account: Uuid,
     pub balance: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PaymentParams {
+    /// The person issuing the payment, must own `from` or be an accountant of it
+    pub actor: Uuid,
     pub to: Uuid,
-    pub from: Uuid,
+    /// Defaults to the account the actor is currently using
+    pub from: Option<Uuid>,
     pub amount: i64,
-    pub force: Option<bool>,
+    /// Shown in the history of both accounts, at most 256 characters
+    pub memo: Option<String>,
+    /// Tag like `rent` or `wages` to filter the history by, at most 32 characters
+    pub category: Option<String>,
+    /// Reason for an admin override, which allows overdrafts and negative amounts. Needs an admin key.
+    pub admin_override: Option<String>,
+    /// Repeating a payment with the same key returns the original result instead of paying again
+    pub idempotency_key: Option<Uuid>,
 }
 
 #[derive(Serialize, Debug)]
 pub struct PaymentParamsReferences<'a> {
+    pub actor: &'a Uuid,
     pub to: &'a Uuid,
-    pub from: &'a Uuid,
+    pub from: Option<&'a Uuid>,
     pub amount: &'a i64,
-    pub force: Option<&'a bool>,
+    pub memo: Option<&'a str>,
+    pub category: Option<&'a str>,
+    pub admin_override: Option<&'a str>,
+    pub idempotency_key: Option<&'a Uuid>,
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ApiKeyParams {
+    pub name: String,
+    #[serde(default)]
+    pub read: bool,
+    #[serde(default)]
+    pub pay: bool,
+    #[serde(default)]
+    pub print: bool,
+    #[serde(default)]
+    pub admin: bool,
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ApiKeyInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub read: bool,
+    pub pay: bool,
+    pub print: bool,
+    pub admin: bool,
+    pub revoked: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreatedApiKey {
+    pub id: Uuid,
+    pub key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct HistoryParams {
+    /// Only return transactions older than this one
+    pub cursor: Option<Uuid>,
+    pub limit: Option<i64>,
+    /// Unix seconds, inclusive
+    pub since: Option<u64>,
+    /// Unix seconds, exclusive
+    pub until: Option<u64>,
+    /// Only return transactions with this category
+    pub category: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct TransactionEntry {
+    pub id: Uuid,
+    /// Always sum up to zero
+    pub postings: Vec<Posting>,
+    pub memo: Option<String>,
+    pub category: Option<String>,
+    /// The transaction this one undoes
+    pub reverses: Option<Uuid>,
+    /// The transactions that undid this one, partially or in full
+    pub reversed_by: Vec<Uuid>,
+    /// Unix seconds
+    pub time: u64,
+}
+
+impl TransactionEntry {
+    /// How much the balance of `account` changed through this transaction
+    #[must_use]
+    pub fn amount_for(&self, account: &Uuid) -> i64 {
+        self.postings
+            .iter()
+            .filter(|posting| posting.account == *account)
+            .map(|posting| posting.amount)
+            .sum()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HistoryPage {
+    pub entries: Vec<TransactionEntry>,
+    pub next_cursor: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateOrganisationParams {
+    pub owner: Uuid,
+    pub name: Option<String>,
+    pub public: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ActorParams {
+    /// The person performing the action
+    pub actor: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct MemberFlagsParams {
+    pub actor: Uuid,
+    pub inviter: Option<bool>,
+    pub accountant: Option<bool>,
+    pub administrator: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct OrganisationMember {
+    pub id: Uuid,
+    pub inviter: bool,
+    pub accountant: bool,
+    pub administrator: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct OrganisationInfo {
+    pub id: Uuid,
+    pub owner: Uuid,
+    pub public: bool,
+    pub balance: i64,
+    pub members: Vec<OrganisationMember>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct FriendInfo {
+    pub id: Uuid,
+    pub discord_id: u64,
+    /// Whether this friend is allowed to spend from your account
+    pub accountant: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct FriendRequest {
+    pub id: Uuid,
+    pub discord_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct FriendRequests {
+    pub incoming: Vec<FriendRequest>,
+    pub outgoing: Vec<FriendRequest>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FriendAccountantParams {
+    pub accountant: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct AccountInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub balance: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AccountDetailsParams {
+    /// The person changing the details, must own the account or administrate its organisation
+    pub actor: Uuid,
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Receipt {
+    /// The id of the transaction in the log
+    pub id: Uuid,
+    pub from: Uuid,
+    pub to: Uuid,
+    pub amount: i64,
+    /// Balance of the sender after the transaction
+    pub from_balance: i64,
+    /// Balance of the receiver after the transaction
+    pub to_balance: i64,
+    /// Unix seconds
+    pub time: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct LeaderboardParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// Also return the rank of this person, even if they aren't on the page
+    pub person: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct LeaderboardEntry {
+    /// Starts at 1, people with the same balance share a rank
+    pub rank: i64,
+    pub id: Uuid,
+    pub discord_id: u64,
+    pub balance: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+    pub person: Option<LeaderboardEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct EconomyStats {
+    /// All money held by accounts, which is everything minted minus everything burned
+    pub money_supply: i64,
+    /// Leaves out the mint and burn accounts
+    pub accounts: i64,
+    pub persons: i64,
+    pub transactions_day: i64,
+    pub transactions_week: i64,
+    /// Money moved by the transactions of the last day
+    pub volume_day: i64,
+    pub volume_week: i64,
+    pub median_balance: i64,
+    /// Inequality of balances, 0 when everyone has the same and 1 when one account has everything
+    pub gini: f64,
+    pub minted: i64,
+    pub burned: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PrintParams {
+    /// Why the money was printed, kept in the transaction log
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BurnParams {
+    /// Why the money was burned, kept in the transaction log
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ReverseParams {
+    pub actor: Uuid,
+    /// Reverses what is left of the transaction if `None`
+    pub amount: Option<i64>,
+    pub reason: Option<String>,
+    /// Reason for an admin override, which skips the permission and balance checks. Needs an admin key.
+    pub admin_override: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ReconcileParams {
+    /// Overwrite drifted balances with what the ledger implies
+    pub repair: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct BalanceMismatch {
+    pub account: Uuid,
+    /// The balance stored on the account
+    pub stored: i64,
+    /// The balance implied by replaying the ledger
+    pub expected: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct ReconcileReport {
+    pub accounts_checked: i64,
+    pub mismatches: Vec<BalanceMismatch>,
+    pub repaired: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AuditParams {
+    pub api_key: Option<Uuid>,
+    pub actor: Option<Uuid>,
+    pub target: Option<Uuid>,
+    /// The HTTP method and route, like `POST /eco/payment`
+    pub action: Option<String>,
+    /// Only return entries older than this one
+    pub cursor: Option<Uuid>,
+    pub limit: Option<i64>,
+    /// Unix seconds, inclusive
+    pub since: Option<u64>,
+    /// Unix seconds, exclusive
+    pub until: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub api_key: Uuid,
+    pub action: String,
+    pub actor: Option<Uuid>,
+    pub target: Option<Uuid>,
+    /// Path and query parameters of the request
+    pub parameters: BTreeMap<String, String>,
+    /// HTTP status code of the response
+    pub status: i16,
+    /// Unix seconds
+    pub time: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    pub next_cursor: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Posting {
+    pub account: Uuid,
+    /// Positive when the account receives money, negative when it pays
+    pub amount: i64,
 }